@@ -1,4 +1,5 @@
 use stunt::prelude::*;
+use stunt::frontend::html::node_id::NodeId;
 
 
 pub enum Message {
@@ -19,7 +20,7 @@ impl Component for App {
         }
     }
 
-    fn callback(&mut self, message: &Message) {
+    fn callback(&mut self, message: &Message, _link: Link<Self>) {
         match message {
             Message::Submit => {
                 if let Some(element) = self.input.cast::<web_sys::HtmlInputElement>() {
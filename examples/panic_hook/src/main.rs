@@ -15,7 +15,7 @@ impl Component for App {
 
     fn create() -> App { App }
 
-    fn callback(&mut self, message: &Message) {
+    fn callback(&mut self, message: &Message, _link: Link<Self>) {
         match message {
             Message::Panic => {
                 panic!("you clicked the panic button");
@@ -36,9 +36,8 @@ impl Component for App {
 
     fn create() -> App { App }
 
-    // TODO: implement once
-    fn once(&mut self, link: Link) {
-        stunt_router::register_callback(move || link.callback::<App>(()));
+    fn once(&mut self, link: Link<Self>) {
+        stunt_router::register_callback(move || link.send_message(()));
     }
 
     fn view(&self, _: ()) -> Html {
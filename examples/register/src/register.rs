@@ -52,7 +52,7 @@ impl Component for Register {
         }
     }
 
-    fn callback(&mut self, message: &Message, link: Link) {
+    fn callback(&mut self, message: &Message, link: Link<Self>) {
         match message {
             Message::Response(response) => {
                 stunt_router::redirect(Route::Registered { user_id: response.user_id });
@@ -63,7 +63,7 @@ impl Component for Register {
                     password: self.password.cast::<web_sys::HtmlInputElement>().expect("failed to cast").value(),
                 };
 
-                register.call(move |response| link.callback::<Register>(Message::Response(response)));
+                register.call(link.callback(Message::Response));
             },
         }
     }
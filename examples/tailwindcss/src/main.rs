@@ -19,7 +19,7 @@ impl Component for App {
         }
     }
 
-    fn callback(&mut self, message: &Message) {
+    fn callback(&mut self, message: &Message, _link: Link<Self>) {
         match message {
             Message::Add => {
                 self.count += 1;
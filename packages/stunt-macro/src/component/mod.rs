@@ -0,0 +1,93 @@
+use syn::parse::{Parse, ParseStream, Error, Result};
+use syn::{ItemFn, FnArg, Type, Pat};
+use syn::spanned::Spanned;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+
+enum Argument {
+    Reference(Box<Pat>, Type),
+    Value(Box<Pat>, Type),
+}
+
+impl Argument {
+    fn new(arg: &FnArg) -> Result<Argument> {
+        match arg {
+            FnArg::Typed(typed) => match &*typed.ty {
+                Type::Reference(reference) if reference.mutability.is_none() => Ok(Argument::Reference(typed.pat.clone(), (*reference.elem).clone())),
+                ty => Ok(Argument::Value(typed.pat.clone(), ty.clone())),
+            },
+            FnArg::Receiver(receiver) => Err(Error::new(receiver.span(), "A function component can not take self")),
+        }
+    }
+}
+
+pub struct FunctionComponent {
+    function: ItemFn,
+    argument: Option<Argument>,
+}
+
+impl Parse for FunctionComponent {
+    fn parse(input: ParseStream) -> Result<FunctionComponent> {
+        let function: ItemFn = input.parse()?;
+
+        if !function.sig.generics.params.is_empty() {
+            return Err(Error::new(function.sig.generics.span(), "A function component can not be generic"));
+        }
+
+        let argument = match function.sig.inputs.len() {
+            0 => None,
+            1 => Some(Argument::new(&function.sig.inputs[0])?),
+            _ => return Err(Error::new(function.sig.inputs.span(), "A function component takes at most one argument, its properties")),
+        };
+
+        Ok(FunctionComponent {
+            function,
+            argument,
+        })
+    }
+}
+
+impl FunctionComponent {
+    pub fn tokens(&self) -> TokenStream {
+        let ItemFn { attrs, vis, sig, block } = &self.function;
+        let name = &sig.ident;
+        let output = &sig.output;
+
+        let (properties, inputs, call) = match &self.argument {
+            Some(Argument::Reference(pat, ty)) => (quote! { #ty }, quote! { #pat: &#ty }, quote! { __stunt_view(&properties) }),
+            Some(Argument::Value(pat, ty)) => (quote! { #ty }, quote! { #pat: #ty }, quote! { __stunt_view(properties) }),
+            None => (quote! { () }, quote! {}, quote! { __stunt_view() }),
+        };
+
+        quote! {
+            #(#attrs)*
+            #vis struct #name {
+                hooks: ::stunt::frontend::hooks::Hooks,
+            }
+
+            impl ::stunt::frontend::Component for #name {
+                type Message = ::stunt::frontend::hooks::Action;
+                type Properties = #properties;
+
+                fn create() -> #name {
+                    #name {
+                        hooks: ::stunt::frontend::hooks::Hooks::default(),
+                    }
+                }
+
+                fn callback(&mut self, action: &::stunt::frontend::hooks::Action, _: ::stunt::frontend::Link<#name>) {
+                    action.run();
+                }
+
+                #[allow(unused_variables)]
+                fn view(&self, properties: #properties) -> ::stunt::frontend::html::Html {
+                    fn __stunt_view(#inputs) #output #block
+
+                    self.hooks.render(|| #call)
+                }
+            }
+        }
+    }
+}
@@ -27,6 +27,10 @@ impl HtmlBuilder {
                     let generics = &node.generics;
                     let index = self.nodes.len();
 
+                    let key = node.key.as_ref().map(|key| quote! {
+                        .with_key(#[allow(unused_braces)] #key)
+                    });
+
                     let events = node.events.iter()
                         .map(|event| event.tokens())
                         .collect::<TokenStream>();
@@ -39,9 +43,9 @@ impl HtmlBuilder {
                         self.nodes.push(quote! {
                             ::stunt::frontend::html::HtmlNode::new(
                                 ::stunt::frontend::html::HtmlKind::Element(::stunt::frontend::html::HtmlElement::new(#str_name.to_string(), ::std::vec![#attributes])),
-                                ::std::sync::Arc::new(::std::vec![#events]),
+                                ::std::rc::Rc::new(::std::vec![#events]),
                                 (),
-                            )
+                            )#key
                         });
                     } else {
                         let properties = node.attributes.iter()
@@ -59,9 +63,9 @@ impl HtmlBuilder {
                             #maybe_typecheck
                             ::stunt::frontend::html::HtmlNode::new(
                                 ::stunt::frontend::html::HtmlKind::create_component::<#name<#(#generics),*>>(String::from(#str_name)),
-                                ::std::sync::Arc::new(::std::vec![#events]),
+                                ::std::rc::Rc::new(::std::vec![#events]),
                                 builder,
-                            )
+                            )#key
                         }});
                     }
 
@@ -81,8 +85,8 @@ impl HtmlBuilder {
                     self.nodes.push(quote! {
                         ::stunt::frontend::html::HtmlNode::new(
                             #[allow(unused_braces)]
-                            ::stunt::frontend::html::HtmlKind::Template(::std::sync::Arc::new(#block)),
-                            ::std::sync::Arc::new(std::vec::Vec::new()),
+                            ::stunt::frontend::html::HtmlKind::Template(::std::rc::Rc::new(#block)),
+                            ::std::rc::Rc::new(std::vec::Vec::new()),
                             (),
                         )
                     });
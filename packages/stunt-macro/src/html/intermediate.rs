@@ -24,6 +24,7 @@ pub struct Node {
     pub events: Vec<Event>,
     pub attributes: Vec<Attribute>,
     pub generics: Vec<Type>,
+    pub key: Option<ExprBlock>,
     pub children: Vec<Kind>,
 }
 
@@ -34,18 +35,20 @@ impl Default for Node {
             events: Vec::default(),
             attributes: Vec::default(),
             generics: Vec::default(),
+            key: None,
             children: Vec::default(),
         }
     }
 }
 
 impl Node {
-    fn new(name: Ident, events: Vec<Event>, attributes: Vec<Attribute>, generics: Vec<Type>, children: Vec<Kind>) -> Node {
+    fn new(name: Ident, events: Vec<Event>, attributes: Vec<Attribute>, generics: Vec<Type>, key: Option<ExprBlock>, children: Vec<Kind>) -> Node {
         Node {
             name,
             events,
             attributes,
             generics,
+            key,
             children,
         }
     }
@@ -82,6 +85,7 @@ impl Node {
             events: open.events,
             attributes: open.attributes,
             generics: open.generics,
+            key: open.key,
             children,
         }
     }
@@ -103,7 +107,7 @@ impl Ir {
         match tags.next() {
             Some(Tag::OpenTag(open)) => {
                 if open.closed {
-                    nodes.push(Kind::Node(Node::new(open.name.clone(), open.events.clone(), open.attributes.clone(), open.generics.clone(), Vec::new())));
+                    nodes.push(Kind::Node(Node::new(open.name.clone(), open.events.clone(), open.attributes.clone(), open.generics.clone(), open.key.clone(), Vec::new())));
                 } else {
                     nodes.push(Kind::Node(Node::parse(tags, open.clone())));
                 }
@@ -36,7 +36,7 @@ impl Event {
 
         quote! {
             #[allow(unused_braces)]
-            (String::from(#name), std::sync::Arc::new(#value)),
+            (String::from(#name), ::std::rc::Rc::new(#value)),
         }
     }
 }
@@ -69,7 +69,7 @@ impl Attribute {
 
         quote! {
             #[allow(unused_braces)]
-            (::std::string::String::from(#name), ::std::rc::Rc::new(#value) as ::std::rc::Rc<dyn ::std::fmt::Display>),
+            (::std::string::String::from(#name), ::std::rc::Rc::new(#value) as ::std::rc::Rc<dyn ::stunt::frontend::html::AttributeValue>),
         }
     }
 
@@ -90,6 +90,7 @@ pub struct OpenTag {
     pub generics: Vec<Type>,
     pub attributes: Vec<Attribute>,
     pub events: Vec<Event>,
+    pub key: Option<ExprBlock>,
     pub closed: bool,
 }
 
@@ -117,18 +118,23 @@ impl Parse for OpenTag {
 
         let mut attributes: Vec<Attribute> = Vec::new();
         let mut events: Vec<Event> = Vec::new();
+        let mut key: Option<ExprBlock> = None;
 
         while !input.peek(Token![>]) && !input.peek(Token![/]) {
             if input.fork().parse::<Ident>().map(|ident| ident.to_string().starts_with("on")).unwrap_or_default() {
                 events.push(input.parse::<Event>()?);
+            } else if input.fork().parse::<Ident>().is_ok_and(|ident| ident == "key") {
+                key = Some(input.parse::<Attribute>()?.value);
             } else {
                 attributes.push(input.parse::<Attribute>()?);
             }
         }
 
-        let closed = input.peek(Token![/])
-            .then(|| { let _ = input.parse::<Token![/]>(); true })
-            .unwrap_or_default();
+        let closed = input.peek(Token![/]);
+
+        if closed {
+            input.parse::<Token![/]>()?;
+        }
 
         input.parse::<Token![>]>()?;
 
@@ -137,6 +143,7 @@ impl Parse for OpenTag {
             generics,
             attributes,
             events,
+            key,
             closed,
         })
     }
@@ -0,0 +1,78 @@
+use syn::parse::{Parse, ParseStream, Error, Result};
+use syn::{ItemImpl, ImplItem, ImplItemFn, Ident};
+use syn::spanned::Spanned;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+
+pub struct Island {
+    item: ItemImpl,
+    view: ImplItemFn,
+}
+
+impl Parse for Island {
+    fn parse(input: ParseStream) -> Result<Island> {
+        let item: ItemImpl = input.parse()?;
+
+        let is_component = item.trait_.as_ref()
+            .and_then(|(_, path, _)| path.segments.last())
+            .is_some_and(|segment| segment.ident == "Component");
+
+        if !is_component {
+            return Err(Error::new(item.span(), "An island must be an implementation of Component"));
+        }
+
+        let view = item.items.iter()
+            .find_map(|item| match item {
+                ImplItem::Fn(function) if function.sig.ident == "view" => Some(function.clone()),
+                _ => None,
+            })
+            .ok_or_else(|| Error::new(item.span(), "An island must implement view"))?;
+
+        Ok(Island {
+            item,
+            view,
+        })
+    }
+}
+
+impl Island {
+    pub fn tokens(&self) -> TokenStream {
+        let mut item = self.item.clone();
+        let self_ty = &item.self_ty;
+        let name = quote! { #self_ty }.to_string();
+        let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+
+        // The view of the component is moved out of the way so the island can wrap what it renders.
+        let mut inner = self.view.clone();
+
+        inner.sig.ident = Ident::new("__stunt_island_view", Span::call_site());
+        inner.vis = syn::Visibility::Inherited;
+
+        let view: ImplItemFn = syn::parse_quote! {
+            fn view(&self, properties: <Self as ::stunt::frontend::Component>::Properties) -> ::stunt::frontend::html::Html {
+                ::stunt::frontend::island::wrap::<Self>(properties, |properties| self.__stunt_island_view(properties))
+            }
+        };
+
+        for item in item.items.iter_mut() {
+            if let ImplItem::Fn(function) = item && function.sig.ident == "view" {
+                *function = view.clone();
+            }
+        }
+
+        quote! {
+            #item
+
+            impl #impl_generics #self_ty #where_clause {
+                #[doc(hidden)]
+                #inner
+            }
+
+            impl #impl_generics ::stunt::frontend::island::Island for #self_ty #where_clause {
+                const NAME: &'static str = ::std::concat!(::std::module_path!(), "::", #name);
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
 #![feature(proc_macro_diagnostic)]
 
 mod properties;
+mod component;
+mod island;
 mod html;
 
 use syn::{parse_macro_input, DeriveInput, Data, Fields};
@@ -10,6 +12,9 @@ use html::codegen::HtmlBuilder;
 use html::tags::Intermediate;
 use html::intermediate::Ir;
 
+use component::FunctionComponent;
+use island::Island;
+
 use properties::{Field, BuilderFields, BuilderFunctions, BuilderTokenType, BuilderFieldsInit, BuilderMarkers, BuilderFieldsBuild, BuilderChildren};
 
 
@@ -28,63 +33,78 @@ pub fn html(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     }
 }
 
+#[proc_macro_attribute]
+pub fn component(_attr: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as FunctionComponent);
+
+    proc_macro::TokenStream::from(input.tokens())
+}
+
+#[proc_macro_attribute]
+pub fn island(_attr: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as Island);
+
+    proc_macro::TokenStream::from(input.tokens())
+}
+
 #[proc_macro_derive(Properties)]
 pub fn properties(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    if let Data::Struct(data) = input.data {
-        if let Fields::Named(fields) = data.fields {
-            let fields = fields.named.into_iter()
-                .filter_map(|field| field.ident.map(|ident| Field::new(ident, field.ty)))
-                .collect::<Vec<Field>>();
-
-            let builder_fields = BuilderFields::new(&fields);
-            let builder_functions = BuilderFunctions::new(&fields);
-            let builder_token_type = BuilderTokenType::new(&fields);
-            let builder_fields_init = BuilderFieldsInit::new(&fields);
-            let builder_markers = BuilderMarkers::new(&fields);
-            let builder_fields_build = BuilderFieldsBuild::new(&fields);
-            let builder_children = BuilderChildren::new(&fields);
-
-            let name = input.ident;
-            let builder_name = syn::Ident::new(&format!("_{}Builder", name), name.span());
-
-            return proc_macro::TokenStream::from(quote! {
-                impl ::stunt::frontend::Buildable for #name {
-                    type Builder = #builder_name;
-
-                    fn builder() -> Self::Builder {
-                        #builder_name {
-                            #builder_fields_init
-                        }
+    if let Data::Struct(data) = input.data
+        && let Fields::Named(fields) = data.fields
+    {
+        let fields = fields.named.into_iter()
+            .filter_map(|field| field.ident.map(|ident| Field::new(ident, field.ty)))
+            .collect::<Vec<Field>>();
+
+        let builder_fields = BuilderFields::new(&fields);
+        let builder_functions = BuilderFunctions::new(&fields);
+        let builder_token_type = BuilderTokenType::new(&fields);
+        let builder_fields_init = BuilderFieldsInit::new(&fields);
+        let builder_markers = BuilderMarkers::new(&fields);
+        let builder_fields_build = BuilderFieldsBuild::new(&fields);
+        let builder_children = BuilderChildren::new(&fields);
+
+        let name = input.ident;
+        let builder_name = syn::Ident::new(&format!("_{}Builder", name), name.span());
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        return proc_macro::TokenStream::from(quote! {
+            impl #impl_generics ::stunt::frontend::Buildable for #name #ty_generics #where_clause {
+                type Builder = #builder_name #ty_generics;
+
+                fn builder() -> Self::Builder {
+                    #builder_name {
+                        #builder_fields_init
                     }
                 }
+            }
 
-                #builder_markers
+            #builder_markers
 
-                #[allow(missing_docs)]
-                pub struct #builder_name {
-                    #builder_fields
-                }
+            #[allow(missing_docs)]
+            pub struct #builder_name #impl_generics #where_clause {
+                #builder_fields
+            }
 
-                impl ::stunt::frontend::PreBuild for #builder_name {
-                    #builder_children
+            impl #impl_generics ::stunt::frontend::PreBuild for #builder_name #ty_generics #where_clause {
+                #builder_children
 
-                    fn build(&self) -> ::std::rc::Rc<dyn ::std::any::Any> {
-                        ::std::rc::Rc::new(#name {
-                            #builder_fields_build
-                        })
-                    }
+                fn build(&self) -> ::std::rc::Rc<dyn ::std::any::Any> {
+                    ::std::rc::Rc::new(#name {
+                        #builder_fields_build
+                    })
                 }
+            }
 
-                impl #builder_name {
-                    #builder_functions
+            impl #impl_generics #builder_name #ty_generics #where_clause {
+                #builder_functions
 
-                    #[allow(missing_docs)]
-                    pub fn typecheck(&self, _token: #builder_token_type) {}
-                }
-            });
-        }
+                #[allow(missing_docs)]
+                pub fn typecheck(&self, _token: #builder_token_type) {}
+            }
+        });
     }
 
     proc_macro::TokenStream::from(syn::Error::new(input.ident.span(), "You can only derive Properties for Structs with Named fields").to_compile_error())
@@ -59,7 +59,7 @@ impl<'a> ToTokens for BuilderFunctions<'a> {
         for field in self.fields.iter() {
             let Field { ident, marker_ident, ty } = &field;
 
-            if ident.to_string() != "children" {
+            if ident != "children" {
                 tokens.extend(quote! {
                     #[allow(missing_docs)]
                     pub fn #ident<Token>(&mut self, token: Token, value: #ty) -> #marker_ident<Token> {
@@ -92,7 +92,7 @@ impl<'a> ToTokens for BuilderTokenType<'a> {
         for field in self.fields.iter() {
             let Field { ident, marker_ident, .. } = &field;
 
-            if ident.to_string() != "children" {
+            if ident != "children" {
                 token_type = quote! { #marker_ident<#token_type> };
             }
         }
@@ -142,7 +142,7 @@ impl<'a> ToTokens for BuilderMarkers<'a> {
         for field in self.fields.iter() {
             let Field { ident, marker_ident, .. } = &field;
 
-            if ident.to_string() != "children" {
+            if ident != "children" {
                 tokens.extend(quote! {
                     #[allow(non_camel_case_types, missing_docs)]
                     pub struct #marker_ident<Token>(Token);
@@ -190,9 +190,9 @@ impl<'a> BuilderChildren<'a> {
 
 impl<'a> ToTokens for BuilderChildren<'a> {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        if self.fields.iter().any(|field| field.ident.to_string() == "children") {
+        if self.fields.iter().any(|field| field.ident == "children") {
             tokens.extend(quote! {
-                fn children(&mut self, children: Children) {
+                fn children(&mut self, children: ::stunt::frontend::html::Children) {
                     self.children.replace(children);
                 }
             });
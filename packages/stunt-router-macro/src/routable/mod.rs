@@ -16,15 +16,16 @@ enum PathNode {
 
 impl PathNode {
     pub fn new(mut node: String) -> PathNode {
-        node.starts_with(':')
-            .then(|| PathNode::Segment(Ident::new(&node.split_off(1), Span::call_site())))
-            .unwrap_or_else(|| PathNode::Static(node.to_string()))
+        match node.starts_with(':') {
+            true => PathNode::Segment(Ident::new(&node.split_off(1), Span::call_site())),
+            false => PathNode::Static(node),
+        }
     }
 
     fn validate(&self, fields: &Fields) -> bool {
         match self {
             PathNode::Static(_) => true,
-            PathNode::Segment(segment) => fields.iter().any(|field| field.ident.clone().map(|ident| ident.to_string() == segment.to_string()).unwrap_or_default()),
+            PathNode::Segment(segment) => fields.iter().any(|field| field.ident.as_ref() == Some(segment)),
         }
     }
 
@@ -209,6 +210,15 @@ impl Variant {
 
         quote! { #(#attributes),*}
     }
+
+    /// The variant if it has a path without segments.
+    fn static_tokens(&self) -> Option<proc_macro2::TokenStream> {
+        let ident = &self.ident;
+
+        self.attributes.iter()
+            .any(|attribute| matches!(attribute, AttributeKind::At { fields, .. } if fields.is_empty()))
+            .then(|| quote! { Self::#ident {}, })
+    }
 }
 
 pub struct Routable {
@@ -257,6 +267,9 @@ impl Routable {
         let path_variants = self.variants.iter()
             .map(|variant| variant.path_tokens());
 
+        let static_variants = self.variants.iter()
+            .filter_map(|variant| variant.static_tokens());
+
         quote! {
             impl ::stunt_router::Routable for #ident {
                 fn route(__path: &[&str]) -> Self {
@@ -270,6 +283,10 @@ impl Routable {
                         #(#path_variants)*
                     }
                 }
+
+                fn static_routes() -> Vec<Self> {
+                    ::std::vec![#(#static_variants)*]
+                }
             }
         }
     }
@@ -9,6 +9,7 @@
 //!
 //! - Route a component to a Path.
 //! - Extract information with Path Segments.
+//! - Route outside of the browser with [`with_location`], e.g. when rendering on the server.
 //!
 //! ## Example
 //! ```rust,no_run
@@ -47,6 +48,7 @@
 //!         name: String,
 //!     },
 //!     #[not_found]
+//!     #[at("/404")]
 //!     NotFound,
 //! }
 //!
@@ -82,6 +84,14 @@ pub use stunt_router_macro::Routable;
 use wasm_bindgen::prelude::*;
 use web_sys::CustomEvent;
 
+use std::cell::RefCell;
+
+
+thread_local! {
+    /// The location set by [`with_location`], it takes precedence over the location of the window.
+    static LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
 
 /// The Routable trait allows an enum to be routed.
 pub trait Routable {
@@ -90,16 +100,22 @@ pub trait Routable {
 
     /// Reconstructs the path of the route.
     fn path(self) -> String;
+
+    /// Returns every route without path segments.
+    fn static_routes() -> Vec<Self> where Self: Sized { Vec::new() }
 }
 
 /// Get the current route.
 #[inline]
 pub fn route<T: Routable>() -> T {
-    let pathname = web_sys::window()
-        .expect("no window found")
-        .location()
-        .pathname()
-        .expect("failed to get pathname");
+    let pathname = LOCATION.with_borrow(|location| location.clone())
+        .unwrap_or_else(|| {
+            web_sys::window()
+                .expect("no window found")
+                .location()
+                .pathname()
+                .expect("failed to get pathname")
+        });
 
     let path = pathname.split('/')
         .collect::<Vec<&str>>();
@@ -107,6 +123,47 @@ pub fn route<T: Routable>() -> T {
     T::route(&path.trim_suffix(&["/"]))
 }
 
+/// Run `f` with `path` as the current location, [`route`] returns the route of `path` instead of the route of the window.
+///
+/// This allows routing where there is no window, e.g. when rendering on the server.
+///
+/// ## Example
+/// ```rust,no_run
+/// # use stunt_router::Routable;
+/// #[derive(Routable, Debug, PartialEq)]
+/// pub enum Route {
+///     #[at("/about")]
+///     About,
+///     #[not_found]
+///     #[at("/404")]
+///     NotFound,
+/// }
+///
+/// # fn main() {
+/// let route = stunt_router::with_location("/about", || stunt_router::route::<Route>());
+///
+/// assert_eq!(route, Route::About);
+/// # }
+/// ```
+pub fn with_location<R>(path: &str, f: impl FnOnce() -> R) -> R {
+    let _location = Location {
+        previous: LOCATION.with_borrow_mut(|location| location.replace(path.to_string())),
+    };
+
+    f()
+}
+
+/// Restores the previous location when dropped, also when the closure passed to [`with_location`] panics.
+struct Location {
+    previous: Option<String>,
+}
+
+impl Drop for Location {
+    fn drop(&mut self) {
+        LOCATION.with_borrow_mut(|location| *location = self.previous.take());
+    }
+}
+
 /// Register a callback for updates on the router.
 #[inline]
 pub fn register_callback(f: impl Fn() + 'static) {
@@ -24,11 +24,19 @@
 //! }
 //! ```
 
+pub(crate) mod transfer;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::frontend::render::Renderer;
+
+#[cfg(target_arch = "wasm32")]
 use crate::http;
 
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 
+use wasm_bindgen::JsValue;
+
 
 /// Represents a server-side service.
 pub trait Service: Serialize + Clone + Sized + 'static {
@@ -36,16 +44,33 @@ pub trait Service: Serialize + Clone + Sized + 'static {
     const PATH: &'static str;
 
     /// The type that the service will output.
-    type Output: Serialize + DeserializeOwned;
+    type Output: Serialize + DeserializeOwned + 'static;
 
     /// Handle a call to the service.
     #[cfg(not(target_arch = "wasm32"))]
     fn handle(self) -> Self::Output;
 
     /// Call the service.
+    ///
+    /// On the server the service is handled right away, and its output is transferred to the browser if the call is
+    /// made while rendering. In the browser a transferred output of the same call is used instead of calling the server.
+    ///
+    /// A call made from a view on the server runs `f` once the renderer polls its futures, the components it
+    /// borrows are released by then. Rendering to a string never does, the output is only transferred.
     fn call(self, f: impl Fn(Self::Output) + 'static) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let output = transfer::handle(self);
+
+            match Renderer::current() {
+                Some((renderer, scope)) => renderer.spawn(scope, async move { f(output) }),
+                None => f(output),
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
         wasm_bindgen_futures::spawn_local(async move {
-            match http::post::<Self, Self::Output>(Self::PATH.to_string(), &self).await {
+            match self.fetch().await {
                 Ok(output) => f(output),
                 Err(err) => {
                     web_sys::console::error_1(&format!("{:?}", err).into());
@@ -54,6 +79,22 @@ pub trait Service: Serialize + Clone + Sized + 'static {
         });
     }
 
+    /// Call the service and wait for its output, see [`call`](Service::call).
+    ///
+    /// This can be used to load the content of a [`Suspense`](crate::frontend::suspense::Suspense), it is then
+    /// streamed from the server and resolved from the transferred output when the page is hydrated.
+    #[allow(async_fn_in_trait)]
+    async fn fetch(self) -> Result<Self::Output, JsValue> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Ok(transfer::handle(self));
+
+        #[cfg(target_arch = "wasm32")]
+        match transfer::take(&self) {
+            Some(output) => Ok(output),
+            None => http::post::<Self, Self::Output>(Self::PATH.to_string(), &self).await,
+        }
+    }
+
     /// An actix-web route handler for the service.
     #[cfg(not(target_arch = "wasm32"))]
     #[cfg(feature = "actix")]
@@ -66,4 +107,82 @@ pub trait Service: Serialize + Clone + Sized + 'static {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::html::Html;
+    use crate::frontend::{Component, Link};
+    use crate::testing::TestRenderer;
+    use crate::ssr;
 
+    use serde::Deserialize;
+
+    use std::rc::Rc;
+
+    #[derive(Serialize, Deserialize, Clone)]
+    struct Greet {
+        name: String,
+    }
+
+    impl Service for Greet {
+        const PATH: &'static str = "/greet";
+
+        type Output = String;
+
+        fn handle(self) -> String {
+            format!("hello {}", self.name)
+        }
+    }
+
+    struct Greeting {
+        greeting: Option<String>,
+    }
+
+    impl Component for Greeting {
+        type Message = String;
+        type Properties = ();
+
+        fn create() -> Greeting {
+            Greeting {
+                greeting: None,
+            }
+        }
+
+        fn callback(&mut self, greeting: &String, _: Link<Greeting>) {
+            self.greeting = Some(greeting.clone());
+        }
+
+        fn view(&self, _: ()) -> Html {
+            if self.greeting.is_none() {
+                let (renderer, scope) = Renderer::current().expect("called from a view");
+
+                Greet { name: String::from("<world>") }.call(move |greeting| renderer.send(scope.clone(), Rc::new(greeting)));
+            }
+
+            Html::wrap("p", Vec::new(), Rc::new(self.greeting.clone().unwrap_or_default()))
+        }
+    }
+
+    #[test]
+    fn calls_from_a_view_run_their_callback_after_the_render() {
+        let renderer = TestRenderer::mount::<Greeting>(());
+
+        assert_eq!(renderer.html(), "<p></p>");
+
+        renderer.settle();
+
+        assert_eq!(renderer.html(), "<p>hello &lt;world&gt;</p>");
+    }
+
+    #[test]
+    fn calls_while_rendering_to_a_string_are_transferred() {
+        let html = ssr::render_to_string::<Greeting>(());
+
+        assert_eq!(html, concat!(
+            "<p></p>",
+            "<script type=\"application/json\" data-stunt-state>",
+            "{\"/greet:{\\\"name\\\":\\\"\\u003cworld>\\\"}\":\"hello \\u003cworld>\"}",
+            "</script>",
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+//! Transfers the outputs of the services called while rendering on the server to the browser.
+//!
+//! The outputs are recorded by their service path and input, and embedded into the page as json. When the page
+//! is hydrated the same calls resolve from the embedded outputs instead of calling the server again.
+
+use crate::backend::Service;
+
+use serde_json::{Map, Value};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+
+/// The recorded outputs, by the key of their call.
+pub(crate) type State = Rc<RefCell<Map<String, Value>>>;
+
+thread_local! {
+    /// The state that records the calls while rendering on the server.
+    static RECORDING: RefCell<Option<State>> = const { RefCell::new(None) };
+
+    /// The state transferred from the server, every output is used once.
+    static TRANSFERRED: RefCell<Map<String, Value>> = RefCell::new(Map::new());
+}
+
+/// The key of a call, made up of the path of the service and its input.
+fn key<S: Service>(service: &S) -> Option<String> {
+    serde_json::to_string(service)
+        .ok()
+        .map(|input| format!("{}:{}", S::PATH, input))
+}
+
+/// Restores the previous recording when dropped, also when the recorded closure panics.
+#[cfg(not(target_arch = "wasm32"))]
+struct Recording {
+    previous: Option<State>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Recording {
+    fn drop(&mut self) {
+        RECORDING.set(self.previous.take());
+    }
+}
+
+/// Record the services called by `f` into `state`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn record<R>(state: &State, f: impl FnOnce() -> R) -> R {
+    let _recording = Recording {
+        previous: RECORDING.with_borrow_mut(|recording| recording.replace(state.clone())),
+    };
+
+    f()
+}
+
+/// Handle a call to the service, the output is recorded if the call was made while recording.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn handle<S: Service>(service: S) -> S::Output {
+    let key = key(&service);
+    let output = service.handle();
+
+    let recording = RECORDING.with_borrow(|recording| recording.clone());
+
+    if let Some((state, key)) = recording.zip(key) && let Ok(value) = serde_json::to_value(&output) {
+        state.borrow_mut().insert(key, value);
+    }
+
+    output
+}
+
+/// The script that embeds the state into the page, empty if nothing was recorded.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn script(state: &State) -> String {
+    let state = state.borrow();
+
+    if state.is_empty() {
+        return String::new();
+    }
+
+    // A `<` can only be part of a string in json, escaping it keeps the script from being closed early.
+    let json = serde_json::to_string(&*state)
+        .unwrap_or_default()
+        .replace('<', "\\u003c");
+
+    format!("<script type=\"application/json\" data-stunt-state>{}</script>", json)
+}
+
+/// Read the state embedded into the page and remove its script, so it isnt mistaken for part of the application.
+pub(crate) fn load(document: &web_sys::Document) {
+    let Ok(Some(script)) = document.query_selector("script[data-stunt-state]") else { return };
+
+    let state = script.text_content()
+        .and_then(|json| serde_json::from_str::<Map<String, Value>>(&json).ok())
+        .unwrap_or_default();
+
+    TRANSFERRED.with_borrow_mut(|transferred| transferred.extend(state));
+
+    script.remove();
+}
+
+/// Take the transferred output of a call.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn take<S: Service>(service: &S) -> Option<S::Output> {
+    let key = key(service)?;
+
+    TRANSFERRED.with_borrow_mut(|transferred| transferred.remove(&key))
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_panic_ends_the_recording() {
+        let outer = State::default();
+        let inner = State::default();
+
+        record(&outer, || {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| record(&inner, || panic!("render failed"))));
+
+            assert!(result.is_err());
+
+            assert!(RECORDING.with_borrow(|recording| recording.as_ref().is_some_and(|state| Rc::ptr_eq(state, &outer))));
+        });
+
+        assert!(RECORDING.with_borrow(|recording| recording.is_none()));
+    }
+}
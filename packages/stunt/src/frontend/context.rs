@@ -0,0 +1,222 @@
+//! Contexts pass values down the component tree without threading them through every
+//! [`Properties`](crate::frontend::Properties) struct.
+//!
+//! A context is keyed by its type and scoped by the [`Path`] of the component that provides it, every
+//! descendant of the provider can look it up with [`use_context`] during its [`view`](crate::frontend::Component::view).
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//! use stunt::frontend::context::{ContextProvider, use_context};
+//!
+//! #[derive(Clone, PartialEq)]
+//! pub struct Theme {
+//!     color: String,
+//! }
+//!
+//! pub struct Title;
+//!
+//! impl Component for Title {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> Title { Title }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         let theme = use_context::<Theme>().expect("no theme provided");
+//!
+//!         html! {
+//!             <h1 style={ format!("color: {}", theme.color) }>
+//!                 { "themed" }
+//!             </h1>
+//!         }
+//!     }
+//! }
+//!
+//! pub struct App;
+//!
+//! impl Component for App {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> App { App }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         html! {
+//!             <ContextProvider<Theme> value={ Theme { color: String::from("red") } }>
+//!                 <Title />
+//!             </ContextProvider>
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::frontend::html::path::Path;
+use crate::frontend::html::{Children, Html};
+use crate::frontend::render::Renderer;
+use crate::frontend::Component;
+
+use stunt_macro::{html, Properties};
+
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+
+/// The values provided by a single component.
+type Provided = HashMap<TypeId, Rc<dyn Any>>;
+
+/// The contexts provided by the components of a renderer and the components consuming them.
+#[derive(Default)]
+pub(crate) struct Contexts {
+    values: RefCell<HashMap<Path, Provided>>,
+    consumers: RefCell<HashMap<TypeId, HashSet<Path>>>,
+}
+
+impl Contexts {
+    /// Provide a value at `scope`, returns true if the value changed and a descendant of `scope` consumes it.
+    pub(crate) fn provide<T: PartialEq + 'static>(&self, scope: Path, value: T) -> bool {
+        let mut values = self.values.borrow_mut();
+        let provided = values.entry(scope.clone()).or_default();
+
+        let changed = provided.get(&TypeId::of::<T>())
+            .and_then(|previous| previous.downcast_ref::<T>())
+            .is_none_or(|previous| *previous != value);
+
+        provided.insert(TypeId::of::<T>(), Rc::new(value));
+
+        changed && self.consumers.borrow()
+            .get(&TypeId::of::<T>())
+            .is_some_and(|consumers| consumers.iter().any(|consumer| consumer.starts_with(&scope)))
+    }
+
+    /// Look up the closest value provided at or above `scope` and subscribe `scope` to changes.
+    pub(crate) fn consume<T: Clone + 'static>(&self, scope: &Path) -> Option<T> {
+        self.consumers.borrow_mut()
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .insert(scope.clone());
+
+        let values = self.values.borrow();
+
+        scope.ancestors()
+            .find_map(|ancestor| values.get(&ancestor).and_then(|provided| provided.get(&TypeId::of::<T>())).cloned())
+            .and_then(|value| value.downcast_ref::<T>().cloned())
+    }
+
+    /// Forget everything provided or consumed by an unmounted component.
+    pub(crate) fn remove(&self, scope: &Path) {
+        self.values.borrow_mut().remove(scope);
+
+        for consumers in self.consumers.borrow_mut().values_mut() {
+            consumers.remove(scope);
+        }
+    }
+}
+
+/// Look up the closest context of type `T` provided by an ancestor of the component that is
+/// currently being viewed.
+///
+/// The component is subscribed to the context and will be re-rendered when the value changes.
+/// Returns [`None`] if no ancestor provides the context or if called outside of a [`view`](Component::view).
+pub fn use_context<T: Clone + 'static>() -> Option<T> {
+    Renderer::current().and_then(|(renderer, scope)| renderer.consume_context::<T>(&scope))
+}
+
+/// The properties of a [`ContextProvider`].
+#[derive(Properties, Clone)]
+pub struct ContextProviderProperties<T: Clone + PartialEq + 'static> {
+    /// The provided value.
+    pub value: T,
+
+    /// The children that can consume the value.
+    pub children: Children,
+}
+
+/// A component that provides a value of type `T` to all of its children.
+pub struct ContextProvider<T: Clone + PartialEq + 'static> {
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Clone + PartialEq + 'static> Component for ContextProvider<T> {
+    type Message = ();
+    type Properties = ContextProviderProperties<T>;
+
+    fn create() -> ContextProvider<T> {
+        ContextProvider {
+            _marker: PhantomData,
+        }
+    }
+
+    fn view(&self, properties: ContextProviderProperties<T>) -> Html {
+        if let Some((renderer, scope)) = Renderer::current() {
+            renderer.provide_context(scope, properties.value);
+        }
+
+        html! {
+            { properties.children }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::html::path::PathNode;
+
+    fn path(names: &[&str]) -> Path {
+        names.iter()
+            .enumerate()
+            .fold(Path::new(), |path, (index, name)| path.concat(PathNode::component(None, index, name.to_string())))
+    }
+
+    #[test]
+    fn consume_finds_the_closest_provider() {
+        let contexts = Contexts::default();
+
+        contexts.provide(path(&["App"]), 1_u32);
+        contexts.provide(path(&["App", "Provider"]), 2_u32);
+
+        assert_eq!(contexts.consume::<u32>(&path(&["App", "Provider", "Consumer"])), Some(2));
+        assert_eq!(contexts.consume::<u32>(&path(&["App", "Sibling"])), Some(1));
+        assert_eq!(contexts.consume::<String>(&path(&["App", "Sibling"])), None);
+    }
+
+    #[test]
+    fn provide_reports_changes_to_consumed_values() {
+        let contexts = Contexts::default();
+        let provider = path(&["App"]);
+
+        assert!(!contexts.provide(provider.clone(), 1_u32));
+
+        contexts.consume::<u32>(&path(&["App", "Consumer"]));
+
+        assert!(!contexts.provide(provider.clone(), 1_u32));
+        assert!(contexts.provide(provider.clone(), 2_u32));
+    }
+
+    #[test]
+    fn provide_ignores_consumers_outside_of_the_provider() {
+        let contexts = Contexts::default();
+
+        contexts.consume::<u32>(&path(&["Other", "Consumer"]));
+
+        contexts.provide(path(&["App"]), 1_u32);
+
+        assert!(!contexts.provide(path(&["App"]), 2_u32));
+    }
+
+    #[test]
+    fn remove_unsubscribes_the_component() {
+        let contexts = Contexts::default();
+        let consumer = path(&["App", "Consumer"]);
+
+        contexts.provide(path(&["App"]), 1_u32);
+        contexts.consume::<u32>(&consumer);
+        contexts.remove(&consumer);
+
+        assert!(!contexts.provide(path(&["App"]), 2_u32));
+    }
+}
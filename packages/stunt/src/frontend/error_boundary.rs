@@ -0,0 +1,378 @@
+//! Error boundaries stop a failing component from taking down the whole application.
+//!
+//! An [`ErrorBoundary`] catches the failures of its descendants and renders a fallback in place of its
+//! children until it is reset. A component fails by calling [`fail`] from its [`view`](crate::frontend::Component::view),
+//! [`Link::fail`](crate::frontend::Link::fail) from anywhere else, or by panicking on targets where panics unwind.
+//!
+//! Panics can only be caught when they unwind, `wasm32-unknown-unknown` aborts on panic by default so failures
+//! in the browser have to be reported explicitly.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//! use stunt::frontend::error_boundary::{ErrorBoundary, Fallback, Reset, fail};
+//!
+//! pub struct Profile;
+//!
+//! impl Component for Profile {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> Profile { Profile }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         match "forty-two".parse::<usize>() {
+//!             Ok(age) => html! {
+//!                 <span>
+//!                     { age }
+//!                 </span>
+//!             },
+//!             Err(err) => fail(err),
+//!         }
+//!     }
+//! }
+//!
+//! pub struct App;
+//!
+//! impl Component for App {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> App { App }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         let fallback = Fallback::new(|failure| html! {
+//!             <div>
+//!                 <p>
+//!                     { failure.to_string() }
+//!                 </p>
+//!                 <button onclick={ Reset }>
+//!                     { "retry" }
+//!                 </button>
+//!             </div>
+//!         });
+//!
+//!         html! {
+//!             <ErrorBoundary fallback={ fallback }>
+//!                 <Profile />
+//!             </ErrorBoundary>
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::frontend::html::path::Path;
+use crate::frontend::html::{Children, Html};
+use crate::frontend::render::Renderer;
+use crate::frontend::{Component, Link};
+
+use stunt_macro::{html, Properties};
+
+use std::collections::{HashMap, HashSet};
+use std::cell::{Cell, RefCell};
+use std::any::Any;
+use std::rc::Rc;
+
+
+/// A failure of a component, caught by the closest [`ErrorBoundary`] above it.
+#[derive(Clone, Debug)]
+pub struct Failure {
+    message: String,
+    scope: Path,
+}
+
+impl Failure {
+    pub(crate) fn new(message: String, scope: Path) -> Failure {
+        Failure {
+            message,
+            scope,
+        }
+    }
+
+    /// Create a failure from the payload of a panic.
+    pub(crate) fn from_panic(payload: &(dyn Any + Send), scope: Path) -> Failure {
+        let message = payload.downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("component panicked"));
+
+        Failure::new(message, scope)
+    }
+
+    /// The error message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The path of the component that failed.
+    pub fn scope(&self) -> &Path {
+        &self.scope
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.write_str(&self.message)
+    }
+}
+
+/// The error boundaries of a renderer and the failures they caught.
+#[derive(Default)]
+pub(crate) struct Boundaries {
+    mounted: RefCell<HashSet<Path>>,
+    failures: RefCell<HashMap<Path, Failure>>,
+    caught: Cell<bool>,
+}
+
+impl Boundaries {
+    /// Register a boundary at `scope`, returns the failure it caught if any.
+    pub(crate) fn register(&self, scope: Path) -> Option<Failure> {
+        let failure = self.failures.borrow().get(&scope).cloned();
+
+        self.mounted.borrow_mut().insert(scope);
+
+        failure
+    }
+
+    /// Hand a failure to the closest boundary above the failing component that isnt showing a fallback
+    /// already, returns the path of the boundary or gives the failure back if there is none.
+    pub(crate) fn catch(&self, failure: Failure) -> Result<Path, Failure> {
+        let mounted = self.mounted.borrow();
+        let mut failures = self.failures.borrow_mut();
+
+        let Some(boundary) = failure.scope.ancestors()
+            .skip(1)
+            .find(|ancestor| mounted.contains(ancestor) && !failures.contains_key(ancestor))
+        else {
+            return Err(failure);
+        };
+
+        failures.insert(boundary.clone(), failure);
+
+        self.caught.set(true);
+
+        Ok(boundary)
+    }
+
+    /// Returns true if a failure was caught since the last call.
+    pub(crate) fn take_caught(&self) -> bool {
+        self.caught.replace(false)
+    }
+
+    /// Clear the failure of the boundary at `scope`, its children are rendered again on the next render.
+    pub(crate) fn reset(&self, scope: &Path) {
+        self.failures.borrow_mut().remove(scope);
+    }
+
+    /// Forget an unmounted boundary.
+    pub(crate) fn remove(&self, scope: &Path) {
+        self.mounted.borrow_mut().remove(scope);
+        self.failures.borrow_mut().remove(scope);
+    }
+}
+
+/// Fail the component that is currently being viewed. The returned [`Html`] is empty and can be returned from the view.
+///
+/// ## Panics
+/// Panics if there is no [`ErrorBoundary`] above the component or if called outside of a [`view`](Component::view).
+pub fn fail(error: impl std::fmt::Display) -> Html {
+    let (renderer, scope) = Renderer::current().expect("fail can only be called in the view of a component");
+
+    renderer.fail(Failure::new(error.to_string(), scope));
+
+    Html::default()
+}
+
+/// Renders the fallback of an [`ErrorBoundary`].
+#[derive(Clone)]
+pub struct Fallback {
+    render: Rc<dyn Fn(&Failure) -> Html>,
+}
+
+impl Fallback {
+    /// Create a new fallback.
+    pub fn new(render: impl Fn(&Failure) -> Html + 'static) -> Fallback {
+        Fallback {
+            render: Rc::new(render),
+        }
+    }
+}
+
+/// The message of an [`ErrorBoundary`], sending it from the fallback renders the children again.
+pub struct Reset;
+
+/// The properties of an [`ErrorBoundary`].
+#[derive(Properties, Clone)]
+pub struct ErrorBoundaryProperties {
+    /// Rendered in place of the children after a failure.
+    pub fallback: Fallback,
+
+    /// The children guarded by the boundary.
+    pub children: Children,
+}
+
+/// A component that catches the failures of its children and renders a fallback instead.
+pub struct ErrorBoundary;
+
+impl Component for ErrorBoundary {
+    type Message = Reset;
+    type Properties = ErrorBoundaryProperties;
+
+    fn create() -> ErrorBoundary { ErrorBoundary }
+
+    fn callback(&mut self, _: &Reset, link: Link<ErrorBoundary>) {
+        link.renderer.reset_boundary(&link.scope);
+    }
+
+    fn view(&self, properties: ErrorBoundaryProperties) -> Html {
+        let failure = Renderer::current().and_then(|(renderer, scope)| renderer.register_boundary(scope));
+
+        match failure {
+            Some(failure) => (properties.fallback.render)(&failure),
+            None => html! {
+                { properties.children }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    thread_local! {
+        static FAILING: Cell<bool> = const { Cell::new(true) };
+        static BOUNDARY: RefCell<Option<Link<ErrorBoundary>>> = const { RefCell::new(None) };
+    }
+
+    struct Panics;
+
+    impl Component for Panics {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Panics { Panics }
+
+        fn view(&self, _: ()) -> Html {
+            panic!("boom");
+        }
+    }
+
+    struct Fails;
+
+    impl Component for Fails {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Fails { Fails }
+
+        fn view(&self, _: ()) -> Html {
+            match FAILING.get() {
+                true => fail("broken"),
+                false => html! {
+                    <span>
+                        { "fine" }
+                    </span>
+                },
+            }
+        }
+    }
+
+    struct FailsLater;
+
+    impl Component for FailsLater {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> FailsLater { FailsLater }
+
+        fn once(&mut self, link: Link<FailsLater>) {
+            link.fail("failed later");
+        }
+
+        fn view(&self, _: ()) -> Html {
+            html! {
+                <span>
+                    { "fine" }
+                </span>
+            }
+        }
+    }
+
+    struct App<C: Component<Properties = ()>> {
+        child: std::marker::PhantomData<C>,
+    }
+
+    impl<C: Component<Properties = ()>> Component for App<C> {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> App<C> { App { child: std::marker::PhantomData } }
+
+        fn view(&self, _: ()) -> Html {
+            let fallback = Fallback::new(|failure| {
+                let (renderer, scope) = Renderer::current().expect("the fallback is rendered by the boundary");
+
+                BOUNDARY.set(Some(Link::new(renderer, scope)));
+
+                html! {
+                    <p>
+                        { failure.to_string() }
+                    </p>
+                }
+            });
+
+            html! {
+                <ErrorBoundary fallback={ fallback }>
+                    <C />
+                </ErrorBoundary>
+            }
+        }
+    }
+
+    fn mount<C: Component<Properties = ()>>() -> Renderer {
+        let renderer = Renderer::detached(App::<C>::create());
+
+        renderer.render();
+
+        renderer
+    }
+
+    #[test]
+    fn a_panicking_view_renders_the_fallback() {
+        let renderer = mount::<Panics>();
+
+        assert_eq!(renderer.tree().inner_html(), "<p>boom</p>");
+    }
+
+    #[test]
+    fn a_failing_view_renders_the_fallback() {
+        let renderer = mount::<Fails>();
+
+        assert_eq!(renderer.tree().inner_html(), "<p>broken</p>");
+    }
+
+    #[test]
+    fn failing_through_the_link_renders_the_fallback() {
+        let renderer = mount::<FailsLater>();
+
+        assert_eq!(renderer.tree().inner_html(), "<p>failed later</p>");
+    }
+
+    #[test]
+    fn reset_renders_the_children_again() {
+        let renderer = mount::<Fails>();
+
+        FAILING.set(false);
+
+        BOUNDARY.take().expect("the fallback was rendered").send_message(Reset);
+
+        assert_eq!(renderer.tree().inner_html(), "<span>fine</span>");
+    }
+
+    #[test]
+    #[should_panic(expected = "uncaught failure")]
+    fn a_failure_without_a_boundary_panics() {
+        Renderer::detached(Fails).render();
+    }
+}
@@ -0,0 +1,244 @@
+//! Hooks give [function components](crate::prelude::component) local state.
+//!
+//! Hooks are identified by the order they are called in, so they must be called unconditionally and in
+//! the same order on every render.
+//!
+//! Function components receive an [`Action`] as their message, so event listeners can run a closure directly.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//! use stunt::frontend::hooks::{use_state, Action};
+//!
+//! #[component]
+//! fn Counter() -> Html {
+//!     let count = use_state(|| 0);
+//!
+//!     let increment = Action::new({
+//!         let count = count.clone();
+//!
+//!         move || count.update(|count| *count += 1)
+//!     });
+//!
+//!     html! {
+//!         <button onclick={ increment }>
+//!             { count.get() }
+//!         </button>
+//!     }
+//! }
+//! ```
+
+use crate::frontend::render::Renderer;
+use crate::frontend::html::Html;
+
+use std::cell::{Cell, RefCell};
+use std::any::Any;
+use std::rc::Rc;
+
+
+thread_local! {
+    static HOOKS: RefCell<Vec<(Hooks, Cell<usize>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A hook and the number of hooks its initialiser created after it.
+type Slot = (Rc<dyn Any>, usize);
+
+/// The hooks of a single function component.
+///
+/// ## Warning
+/// This struct is not meant to be used outside the framework.
+#[derive(Clone, Default)]
+pub struct Hooks {
+    slots: Rc<RefCell<Vec<Slot>>>,
+}
+
+/// Removes the hooks of a function component from the stack when its view returns or panics.
+struct Rendering;
+
+impl Drop for Rendering {
+    fn drop(&mut self) {
+        HOOKS.with_borrow_mut(|hooks| hooks.pop());
+    }
+}
+
+impl Hooks {
+    /// Run the view of a function component, hooks called by `view` are stored in `self`.
+    pub fn render(&self, view: impl FnOnce() -> Html) -> Html {
+        HOOKS.with_borrow_mut(|hooks| hooks.push((self.clone(), Cell::new(0))));
+
+        let _rendering = Rendering;
+
+        view()
+    }
+
+    /// Get the next hook of the current function component, or create it with `init`.
+    ///
+    /// `init` runs without anything borrowed, so it can use hooks itself. The hooks it creates are stored after
+    /// its own and skipped on later renders, when `init` doesnt run anymore.
+    fn next<T: 'static>(init: impl FnOnce() -> T) -> Rc<T> {
+        let (hooks, slot) = HOOKS.with_borrow(|hooks| {
+            let (hooks, index) = hooks.last().expect("hooks can only be used in the view of a function component");
+
+            (hooks.clone(), index.get())
+        });
+
+        let existing = hooks.slots.borrow().get(slot).cloned();
+
+        let hook = match existing {
+            Some((hook, nested)) => {
+                Hooks::advance(slot + 1 + nested);
+
+                hook
+            },
+            None => {
+                hooks.slots.borrow_mut().push((Rc::new(()), 0));
+
+                Hooks::advance(slot + 1);
+
+                let hook = Rc::new(init()) as Rc<dyn Any>;
+                let nested = hooks.slots.borrow().len() - slot - 1;
+
+                hooks.slots.borrow_mut()[slot] = (hook.clone(), nested);
+
+                hook
+            },
+        };
+
+        hook.downcast::<T>()
+            .expect("hooks must be called in the same order on every render")
+    }
+
+    /// Continue with the hook at `index` of the current function component.
+    fn advance(index: usize) {
+        HOOKS.with_borrow(|hooks| hooks.last().map(|(_, next)| next.set(index)));
+    }
+}
+
+/// The message of a function component, the closure is run when the action is received.
+#[derive(Clone)]
+pub struct Action {
+    run: Rc<dyn Fn()>,
+}
+
+impl Action {
+    /// Create a new action.
+    pub fn new(run: impl Fn() + 'static) -> Action {
+        Action {
+            run: Rc::new(run),
+        }
+    }
+
+    /// Run the action.
+    pub fn run(&self) {
+        (self.run)()
+    }
+}
+
+/// State local to a function component, setting the state re-renders the component.
+pub struct State<T> {
+    value: Rc<RefCell<T>>,
+    renderer: Renderer,
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> State<T> {
+        State {
+            value: self.value.clone(),
+            renderer: self.renderer.clone(),
+        }
+    }
+}
+
+impl<T: 'static> State<T> {
+    /// Get a clone of the state.
+    pub fn get(&self) -> T
+    where
+        T: Clone
+    {
+        self.value.borrow().clone()
+    }
+
+    /// Replace the state and re-render the component.
+    pub fn set(&self, value: T) {
+        self.update(|state| *state = value);
+    }
+
+    /// Mutate the state in place and re-render the component.
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.value.borrow_mut());
+
+        self.renderer.render();
+    }
+}
+
+/// Create state that is kept between renders of a function component. `init` is only called on the first render.
+pub fn use_state<T: 'static>(init: impl FnOnce() -> T) -> State<T> {
+    let (renderer, _) = Renderer::current().expect("hooks can only be used in the view of a function component");
+
+    State {
+        value: Hooks::next(|| RefCell::new(init())),
+        renderer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn hooks_keep_their_value_between_renders() {
+        let hooks = Hooks::default();
+        let inits = Cell::new(0);
+
+        let first = Rc::new(Cell::new(0));
+        let second = Rc::new(Cell::new(0));
+
+        for (counter, value) in [(&first, 1), (&second, 2)] {
+            hooks.render(|| {
+                let hook = Hooks::next(|| {
+                    inits.set(inits.get() + 1);
+
+                    Cell::new(value)
+                });
+
+                counter.set(hook.get());
+
+                Html::default()
+            });
+        }
+
+        assert_eq!((first.get(), second.get(), inits.get()), (1, 1, 1));
+    }
+
+    #[test]
+    fn initialisers_can_use_hooks() {
+        let hooks = Hooks::default();
+        let values = RefCell::new(Vec::new());
+
+        for _ in 0..2 {
+            hooks.render(|| {
+                let outer = Hooks::next(|| *Hooks::next(|| 1) + 1);
+                let after = Hooks::next(|| 3);
+
+                values.borrow_mut().push((*outer, *after));
+
+                Html::default()
+            });
+        }
+
+        assert_eq!(*values.borrow(), vec![(2, 3), (2, 3)]);
+        assert_eq!(hooks.slots.borrow().len(), 3);
+    }
+
+    #[test]
+    fn a_panicking_view_leaves_no_hooks_behind() {
+        let hooks = Hooks::default();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| hooks.render(|| panic!("failing view"))));
+
+        assert!(result.is_err());
+        assert!(HOOKS.with_borrow(|hooks| hooks.is_empty()));
+    }
+}
@@ -3,19 +3,19 @@
 pub mod node_id;
 pub mod path;
 
-use crate::frontend::virtual_dom::{VirtualNode, VirtualKind, VirtualElement};
+use crate::frontend::virtual_dom::{VirtualNode, VirtualKind, VirtualElement, Binding, Key};
+use crate::frontend::signal::{Signal, Memo};
 use crate::frontend::render::Renderer;
 
 use crate::frontend::html::path::{Path, PathNode};
-use crate::frontend::{Component, BaseComponent, PreBuild};
+use crate::frontend::{Component, PreBuild};
+
+use crate::frontend::render::ComponentRef;
 
 use std::cell::RefCell;
-use std::sync::Arc;
 use std::any::Any;
 use std::rc::Rc;
 
-use spin::Mutex;
-
 macro_rules! impl_t {
     ($($t:ty),+) => {
         trait NonHtmlTemplate {}
@@ -37,7 +37,25 @@ pub trait Template {
 
 impl<T: std::fmt::Display + NonHtmlTemplate + Clone> Template for T {
     fn template(&self, _: Renderer, _: Path, scope: Path) -> Vec<VirtualNode> {
-        vec![VirtualNode::new(Arc::new(Vec::new()), VirtualKind::Template(format!("{}", self)), scope)]
+        vec![VirtualNode::new(Rc::new(Vec::new()), VirtualKind::Template(format!("{}", self)), scope)]
+    }
+}
+
+impl<T: std::fmt::Display + 'static> Template for Signal<T> {
+    fn template(&self, _: Renderer, _: Path, scope: Path) -> Vec<VirtualNode> {
+        let signal = self.clone();
+
+        vec![VirtualNode::new(Rc::new(Vec::new()), VirtualKind::Template(self.with(|value| value.to_string())), scope)
+            .with_bindings(vec![Binding::Text(Rc::new(move || signal.with(|value| value.to_string())))])]
+    }
+}
+
+impl<T: std::fmt::Display + PartialEq + 'static> Template for Memo<T> {
+    fn template(&self, _: Renderer, _: Path, scope: Path) -> Vec<VirtualNode> {
+        let memo = self.clone();
+
+        vec![VirtualNode::new(Rc::new(Vec::new()), VirtualKind::Template(self.with(|value| value.to_string())), scope)
+            .with_bindings(vec![Binding::Text(Rc::new(move || memo.with(|value| value.to_string())))])]
     }
 }
 
@@ -47,6 +65,16 @@ impl Template for Children {
     }
 }
 
+impl Template for Vec<Html> {
+    fn template(&self, renderer: Renderer, path: Path, scope: Path) -> Vec<VirtualNode> {
+        self.iter()
+            .flat_map(|html| html.refs.iter().map(move |node_ref| (html, node_ref)))
+            .enumerate()
+            .flat_map(|(child_index, (html, node_ref))| html.nodes[node_ref.index].render(renderer.clone(), scope.clone(), path.clone(), html.nodes.clone(), node_ref.refs.clone(), child_index))
+            .collect::<Vec<VirtualNode>>()
+    }
+}
+
 impl Template for Html {
     fn template(&self, renderer: Renderer, path: Path, scope: Path) -> Vec<VirtualNode> {
         let children = Children::new(self.nodes.clone(), self.refs.clone(), scope);
@@ -86,25 +114,67 @@ impl Children {
     }
 }
 
+/// The attribute value trait allows a type to be used as the value of an attribute.
+///
+/// ## Warning
+/// For the time being this trait is not supposed to be implemented outside the framework.
+pub trait AttributeValue {
+    /// The current value of the attribute.
+    fn value(&self) -> String;
+
+    /// Reads the value while tracking the signals it depends on, if the value is reactive.
+    fn reactive(&self) -> Option<Rc<dyn Fn() -> String>> { None }
+}
+
+impl<T: std::fmt::Display> AttributeValue for T {
+    fn value(&self) -> String { self.to_string() }
+}
+
+impl<T: std::fmt::Display + 'static> AttributeValue for Signal<T> {
+    fn value(&self) -> String { self.with(|value| value.to_string()) }
+
+    fn reactive(&self) -> Option<Rc<dyn Fn() -> String>> {
+        let signal = self.clone();
+
+        Some(Rc::new(move || signal.value()))
+    }
+}
+
+impl<T: std::fmt::Display + PartialEq + 'static> AttributeValue for Memo<T> {
+    fn value(&self) -> String { self.with(|value| value.to_string()) }
+
+    fn reactive(&self) -> Option<Rc<dyn Fn() -> String>> {
+        let memo = self.clone();
+
+        Some(Rc::new(move || memo.value()))
+    }
+}
+
 /// Represents a html element.
 #[derive(Clone)]
 pub struct HtmlElement {
     name: String,
-    attributes: Vec<(String, Rc<dyn std::fmt::Display>)>,
+    attributes: Vec<(String, Rc<dyn AttributeValue>)>,
 }
 
 impl HtmlElement {
     /// Create a new html element.
-    pub fn new(name: String, attributes: Vec<(String, Rc<dyn std::fmt::Display>)>) -> HtmlElement {
+    pub fn new(name: String, attributes: Vec<(String, Rc<dyn AttributeValue>)>) -> HtmlElement {
         HtmlElement {
             name,
             attributes,
         }
     }
 
-    fn attributes(&self) -> String {
+    fn attributes(&self) -> Vec<(String, String)> {
         self.attributes.iter()
-            .map(|(key, value)| format!("{}=\"{}\"", key, value))
+            .map(|(key, value)| (key.clone(), value.value()))
+            .collect()
+    }
+
+    fn bindings(&self) -> Vec<Binding> {
+        self.attributes.iter()
+            .filter_map(|(key, value)| value.reactive().map(|read| Binding::Attribute(key.clone(), read)))
             .collect()
     }
 }
@@ -117,12 +187,12 @@ impl HtmlElement {
 pub enum HtmlKind {
     #[allow(missing_docs)]
     Component {
-        builder: fn() -> Arc<Mutex<dyn BaseComponent + Send + Sync>>,
+        builder: fn() -> ComponentRef,
         name: String,
     },
 
     #[allow(missing_docs)]
-    Template(Arc<dyn Template>),
+    Template(Rc<dyn Template>),
 
     #[allow(missing_docs)]
     Element(HtmlElement),
@@ -130,9 +200,9 @@ pub enum HtmlKind {
 
 impl HtmlKind {
     /// Create a component of the generic type
-    pub fn create_component<T: Component + Send + Sync>(name: String) -> HtmlKind {
+    pub fn create_component<T: Component>(name: String) -> HtmlKind {
         HtmlKind::Component {
-            builder: || Arc::new(Mutex::new(T::create())),
+            builder: || Rc::new(RefCell::new(T::create())),
             name,
         }
     }
@@ -142,33 +212,44 @@ impl HtmlKind {
         renderer: Renderer,
         path: Path,
         scope: Path,
-        properties: Rc<RefCell<dyn PreBuild>>,
-        callbacks: Arc<Vec<(String, Arc<dyn Any + Send + Sync>)>>,
+        node: &HtmlNode,
         children: Children,
         child_index: usize,
     ) -> Vec<VirtualNode> {
         match self {
             HtmlKind::Component { builder, name } => {
-                let path = path.concat(PathNode::new(child_index, name.clone()));
+                let component_node = PathNode::component(node.key.clone(), child_index, name.clone());
+                let path = path.concat(component_node.clone());
+
+                let component = renderer.get_or_insert(&path, builder);
+
+                let nodes = renderer.view(&component, &path, node.properties.borrow().build())
+                    .render(renderer.clone(), path.clone());
 
-                renderer.get_or_insert(&path, builder)
-                    .lock()
-                    .base_view(properties.borrow().build())
-                    .render(renderer, path)
+                // The nodes of a keyed component move together with the component.
+                let nodes = match &node.key {
+                    Some(_) => nodes.into_iter()
+                        .enumerate()
+                        .map(|(index, child)| child.with_key(Some(Key::Component(component_node.clone(), index))))
+                        .collect(),
+                    None => nodes,
+                };
+
+                renderer.divert(&path, nodes)
             },
             HtmlKind::Template(templates) => {
-                let path = path.concat(PathNode::new(child_index, String::from("template")));
+                let path = path.concat(PathNode::template(child_index));
 
                 templates.template(renderer, path.clone(), scope.clone())
             },
             HtmlKind::Element(element) => {
-                let path = path.concat(PathNode::new(child_index, String::from("element")));
+                let path = path.concat(PathNode::element(node.key.clone(), child_index));
 
                 vec![VirtualNode::new(
-                    callbacks,
-                    VirtualKind::Element(VirtualElement::new(element.name.clone(), element.attributes(), Arc::new(children.render(renderer, path.clone())))),
+                    node.callbacks.clone(),
+                    VirtualKind::Element(VirtualElement::new(element.name.clone(), element.attributes(), Rc::new(children.render(renderer, path.clone())))),
                     scope,
-                )]
+                ).with_bindings(element.bindings()).with_key(node.key.clone().map(Key::Node))]
             },
         }
     }
@@ -178,21 +259,34 @@ impl HtmlKind {
 #[derive(Clone)]
 pub struct HtmlNode {
     kind: HtmlKind,
-    callbacks: Arc<Vec<(String, Arc<dyn Any + Send + Sync>)>>,
+    callbacks: Rc<Vec<(String, Rc<dyn Any>)>>,
     properties: Rc<RefCell<dyn PreBuild>>,
+    key: Option<String>,
 }
 
 impl HtmlNode {
     /// Create a new [`HtmlNode`].
     pub fn new<T: PreBuild + 'static>(
         kind: HtmlKind,
-        callbacks: Arc<Vec<(String, Arc<dyn Any + Send + Sync>)>>,
+        callbacks: Rc<Vec<(String, Rc<dyn Any>)>>,
         properties: T,
     ) -> HtmlNode {
         HtmlNode {
             kind,
             callbacks,
             properties: Rc::new(RefCell::new(properties)),
+            key: None,
+        }
+    }
+
+    /// Identify the node by a key instead of its position among its siblings.
+    ///
+    /// Components keep their state when keyed siblings are inserted, removed or reordered, keys should
+    /// therefore be unique among siblings.
+    pub fn with_key(self, key: impl std::fmt::Display) -> HtmlNode {
+        HtmlNode {
+            key: Some(key.to_string()),
+            ..self
         }
     }
 
@@ -209,7 +303,7 @@ impl HtmlNode {
 
         self.properties.borrow_mut().children(children.clone());
 
-        self.kind.render(renderer, path, scope, self.properties.clone(), self.callbacks.clone(), children, child_index)
+        self.kind.render(renderer, path, scope, self, children, child_index)
     }
 }
 
@@ -242,11 +336,21 @@ impl Html {
     /// Create a new Html tree
     pub fn new(nodes: Rc<Vec<HtmlNode>>, refs: Rc<Vec<NodeRef>>) -> Html {
         Html {
-            nodes: nodes,
+            nodes,
             refs,
         }
     }
 
+    /// Create html of an element named `name` that contains `content`.
+    pub(crate) fn wrap(name: &str, attributes: Vec<(String, Rc<dyn AttributeValue>)>, content: Rc<dyn Template>) -> Html {
+        let nodes = vec![
+            HtmlNode::new(HtmlKind::Element(HtmlElement::new(name.to_string(), attributes)), Rc::new(Vec::new()), ()),
+            HtmlNode::new(HtmlKind::Template(content), Rc::new(Vec::new()), ()),
+        ];
+
+        Html::new(Rc::new(nodes), Rc::new(vec![NodeRef::new(0, Rc::new(vec![NodeRef::new(1, Rc::new(Vec::new()))]))]))
+    }
+
     pub(crate) fn render(self, renderer: Renderer, path: Path) -> Vec<VirtualNode> {
         self.refs.iter()
             .enumerate()
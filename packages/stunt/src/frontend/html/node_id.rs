@@ -27,7 +27,14 @@ impl NodeId {
     }
 
     /// Cast the [`NodeId`] into a Element
+    ///
+    /// Without a DOM, e.g. in a [`TestRenderer`](crate::testing::TestRenderer), there is no element and [`None`] is
+    /// returned, the element can be found with [`TestRenderer::find_by_id`](crate::testing::TestRenderer::find_by_id).
     pub fn cast<T: JsCast>(&self) -> Option<T> {
+        if cfg!(not(target_arch = "wasm32")) {
+            return None;
+        }
+
         let window = web_sys::window().expect("no global window exists");
         let document = window.document().expect("should have a document on window");
 
@@ -1,35 +1,93 @@
 //! A [`Path`] describes a path from root to a node.
 
 
+/// Identifies a node among its siblings.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) enum Identity {
+    /// The position of the node.
+    Index(usize),
+
+    /// The `key` of the node, which stays the same when siblings are inserted, removed or reordered.
+    Key(String),
+}
+
+/// What a node is, components are identified by their name.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Component(String),
+    Element,
+    Template,
+
+    /// The content of a streamed suspense.
+    Deferred,
+}
+
+impl Kind {
+    fn name(&self) -> &str {
+        match self {
+            Kind::Component(name) => name,
+            Kind::Element => "element",
+            Kind::Template => "template",
+            Kind::Deferred => "deferred",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub(crate) struct PathNode {
-    index: usize,
-    name: String,
+    identity: Identity,
+    kind: Kind,
 }
 
 impl PathNode {
-    pub(crate) fn new(index: usize, name: String) -> PathNode {
+    /// Create a component identified by its key, or by its index if it has no key.
+    pub(crate) fn component(key: Option<String>, index: usize, name: String) -> PathNode {
+        PathNode::keyed(key, index, Kind::Component(name))
+    }
+
+    /// Create an element identified by its key, or by its index if it has no key.
+    pub(crate) fn element(key: Option<String>, index: usize) -> PathNode {
+        PathNode::keyed(key, index, Kind::Element)
+    }
+
+    pub(crate) fn template(index: usize) -> PathNode {
+        PathNode::keyed(None, index, Kind::Template)
+    }
+
+    pub(crate) fn deferred(index: usize) -> PathNode {
+        PathNode::keyed(None, index, Kind::Deferred)
+    }
+
+    fn keyed(key: Option<String>, index: usize, kind: Kind) -> PathNode {
         PathNode {
-            index,
-            name,
+            identity: key.map(Identity::Key).unwrap_or(Identity::Index(index)),
+            kind,
         }
     }
 }
 
-/// Describes a path from root to a node. This is used to build an XPath query during
-/// reconciliation.
+/// Describes a path from root to a node. This is used to identify components between renders.
 #[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
 pub struct Path {
     nodes: Vec<PathNode>,
 }
 
+impl std::fmt::Display for PathNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match &self.identity {
+            Identity::Index(index) => write!(f, "/{}[{}]", self.kind.name(), index),
+            Identity::Key(key) => write!(f, "/{}[key={}]", self.kind.name(), key),
+        }
+    }
+}
+
 impl std::fmt::Display for Path {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        let xpath = self.nodes.iter()
-            .map(|node| format!("/*[{}]", node.index + 1))
-            .collect::<String>();
+        for node in self.nodes.iter() {
+            write!(f, "{}", node)?;
+        }
 
-        f.write_str(&xpath)
+        Ok(())
     }
 }
 
@@ -40,6 +98,32 @@ impl Path {
         }
     }
 
+    /// Returns true if `self` is `other` or a descendant of `other`.
+    pub(crate) fn starts_with(&self, other: &Path) -> bool {
+        self.nodes.starts_with(&other.nodes)
+    }
+
+    /// Iterate over `self` and all of its ancestors, starting with `self` and ending with the root.
+    pub(crate) fn ancestors(&self) -> impl Iterator<Item = Path> + '_ {
+        (0..=self.nodes.len()).rev().map(|len| Path { nodes: self.nodes[..len].to_vec() })
+    }
+
+    /// The component closest to `ancestor` on the way down to `self`, if `self` is below `ancestor`.
+    pub(crate) fn child_component(&self, ancestor: &Path) -> Option<Path> {
+        if !self.starts_with(ancestor) {
+            return None;
+        }
+
+        (ancestor.nodes.len() + 1..=self.nodes.len())
+            .find(|len| matches!(self.nodes[len - 1].kind, Kind::Component(_)))
+            .map(|len| Path { nodes: self.nodes[..len].to_vec() })
+    }
+
+    /// The name of the last node, e.g. the name of a component.
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.nodes.last().map(|node| node.kind.name())
+    }
+
     pub(crate) fn concat(mut self, node: PathNode) -> Path {
         self.nodes.push(node);
 
@@ -49,4 +133,29 @@ impl Path {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn child_component_skips_elements_and_templates() {
+        let app = Path::new().concat(PathNode::component(None, 0, String::from("App")));
+        let path = app.clone()
+            .concat(PathNode::element(None, 0))
+            .concat(PathNode::component(Some(String::from("a")), 1, String::from("Item")))
+            .concat(PathNode::template(0));
+
+        let component = path.child_component(&app).unwrap();
 
+        assert_eq!(component.to_string(), "/App[0]/element[0]/Item[key=a]");
+        assert_eq!(component.name(), Some("Item"));
+    }
+
+    #[test]
+    fn components_are_told_apart_by_kind_not_name() {
+        let path = Path::new().concat(PathNode::component(None, 0, String::from("element")));
+
+        assert_eq!(path.child_component(&Path::new()), Some(path.clone()));
+        assert_eq!(Path::new().concat(PathNode::element(None, 0)).child_component(&Path::new()), None);
+    }
+}
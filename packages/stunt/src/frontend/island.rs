@@ -0,0 +1,223 @@
+//! Islands are the interactive parts of an otherwise static page.
+//!
+//! A component marked with [`island`](crate::prelude::island) is wrapped in an element that carries its name and
+//! serialized properties when it is rendered on the server. In the browser [`Islands::hydrate`] hydrates every island
+//! with its own [`Renderer`], the html around the islands is left untouched and its components are never run.
+//!
+//! The properties of an island are serialized, so they have to implement [`Serialize`] and [`DeserializeOwned`] and
+//! cant contain children.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//! use stunt::frontend::island::Islands;
+//! use serde::{Serialize, Deserialize};
+//!
+//! #[derive(Properties, Clone, Serialize, Deserialize)]
+//! pub struct CounterProperties {
+//!     start: usize,
+//! }
+//!
+//! pub struct Counter {
+//!     count: usize,
+//! }
+//!
+//! #[island]
+//! impl Component for Counter {
+//!     type Message = ();
+//!     type Properties = CounterProperties;
+//!
+//!     fn create() -> Counter { Counter { count: 0 } }
+//!
+//!     fn callback(&mut self, _: &(), _: Link<Counter>) {
+//!         self.count += 1;
+//!     }
+//!
+//!     fn view(&self, properties: CounterProperties) -> Html {
+//!         html! {
+//!             <button onclick={ () }>
+//!                 { properties.start + self.count }
+//!             </button>
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     Islands::new()
+//!         .register::<Counter>()
+//!         .hydrate();
+//! }
+//! ```
+
+use crate::frontend::html::{Html, AttributeValue};
+use crate::frontend::html::path::Path;
+use crate::frontend::render::{self, Renderer, RenderError, ErrorHandler};
+use crate::frontend::Component;
+use crate::backend::transfer;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use wasm_bindgen::prelude::*;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+
+/// A component that is hydrated on its own, implemented by the [`island`](crate::prelude::island) macro.
+pub trait Island: Component<Properties: Serialize + DeserializeOwned> {
+    /// The name the island is registered by, the path of the component including its module.
+    const NAME: &'static str;
+}
+
+/// Wrap the html `view` renders in the element the island is hydrated from, unless it is the root of the renderer
+/// hydrating it. The properties are only serialized when the element is rendered.
+#[doc(hidden)]
+pub fn wrap<T: Island>(properties: T::Properties, view: impl FnOnce(T::Properties) -> Html) -> Html {
+    let hydrating = Renderer::current().is_some_and(|(renderer, scope)| !renderer.is_detached() && scope == Path::new());
+
+    if hydrating {
+        return view(properties);
+    }
+
+    let serialized = serde_json::to_string(&properties).expect("failed to serialize the properties of an island");
+
+    let attributes = vec![
+        (String::from("style"), Rc::new("display: contents") as Rc<dyn AttributeValue>),
+        (String::from("data-stunt-island"), Rc::new(T::NAME)),
+        (String::from("data-stunt-props"), Rc::new(serialized)),
+    ];
+
+    Html::wrap("stunt-island", attributes, Rc::new(view(properties)))
+}
+
+type Hydrate = fn(web_sys::HtmlElement, &str, ErrorHandler) -> Result<Renderer, RenderError>;
+
+/// The islands of a page, see the [module documentation](self).
+#[derive(Default)]
+pub struct Islands {
+    islands: HashMap<&'static str, Hydrate>,
+    on_error: Option<ErrorHandler>,
+}
+
+impl Islands {
+    /// Create an empty set of islands.
+    pub fn new() -> Islands {
+        Islands::default()
+    }
+
+    /// Register an island, it is hydrated wherever it was rendered on the server.
+    ///
+    /// ## Panics
+    /// Panics if an island with the same name is registered already.
+    pub fn register<T: Island>(mut self) -> Islands {
+        let hydrate: Hydrate = |root, properties, handler| {
+            let properties = serde_json::from_str::<T::Properties>(properties)
+                .map_err(|err| RenderError::Island(format!("invalid properties of {}: {}", T::NAME, err)))?;
+
+            Ok(Renderer::hydrate_with_root::<T>(root, properties, handler))
+        };
+
+        if self.islands.insert(T::NAME, hydrate).is_some() {
+            panic!("island {} is registered twice", T::NAME);
+        }
+
+        self
+    }
+
+    /// Install a handler for the errors of the islands, see [`Renderer::on_error`]. It also receives the islands that
+    /// couldnt be hydrated, they are logged to the console otherwise.
+    pub fn on_error(self, handler: impl Fn(RenderError) + 'static) -> Islands {
+        Islands {
+            on_error: Some(Rc::new(handler)),
+            ..self
+        }
+    }
+
+    /// Hydrate every registered island of the page and return their renderers.
+    ///
+    /// Islands inside of other islands are hydrated together with the island they are in.
+    pub fn hydrate(self) -> Vec<Renderer> {
+        let handler = self.on_error.clone().unwrap_or_else(|| Rc::new(render::log_error));
+
+        let window = web_sys::window().expect("no global window exists");
+        let document = window.document().expect("should have a document on window");
+
+        transfer::load(&document);
+
+        let Ok(roots) = document.query_selector_all("[data-stunt-island]") else { return Vec::new() };
+
+        let mut renderers = Vec::new();
+
+        for index in 0..roots.length() {
+            let Some(root) = roots.item(index).and_then(|root| root.dyn_into::<web_sys::HtmlElement>().ok()) else { continue };
+
+            let nested = root.parent_element()
+                .and_then(|parent| parent.closest("[data-stunt-island]").ok().flatten())
+                .is_some();
+
+            let name = root.get_attribute("data-stunt-island").unwrap_or_default();
+            let properties = root.get_attribute("data-stunt-props").unwrap_or_default();
+
+            match self.islands.get(name.as_str()) {
+                Some(_) if nested => {},
+                Some(hydrate) => match hydrate(root, &properties, handler.clone()) {
+                    Ok(renderer) => renderers.push(renderer),
+                    Err(err) => handler(err),
+                },
+                None => handler(RenderError::Island(format!("{} isnt registered", name))),
+            }
+        }
+
+        renderers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::ssr;
+
+    use serde::Deserialize;
+
+    #[derive(Properties, Clone, Serialize, Deserialize)]
+    struct CounterProperties {
+        start: usize,
+    }
+
+    struct Counter;
+
+    #[island]
+    impl Component for Counter {
+        type Message = ();
+        type Properties = CounterProperties;
+
+        fn create() -> Counter { Counter }
+
+        fn view(&self, properties: CounterProperties) -> Html {
+            Html::wrap("button", Vec::new(), Rc::new(properties.start))
+        }
+    }
+
+    #[test]
+    fn islands_are_named_by_their_module() {
+        assert_eq!(Counter::NAME, "stunt::frontend::island::tests::Counter");
+    }
+
+    #[test]
+    fn islands_are_wrapped_with_their_properties() {
+        assert_eq!(ssr::render_to_string::<Counter>(CounterProperties { start: 3 }), concat!(
+            "<stunt-island style=\"display: contents\" data-stunt-island=\"stunt::frontend::island::tests::Counter\" ",
+            "data-stunt-props=\"{&quot;start&quot;:3}\"><button>3</button></stunt-island>",
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "registered twice")]
+    fn islands_are_registered_once() {
+        Islands::new()
+            .register::<Counter>()
+            .register::<Counter>();
+    }
+}
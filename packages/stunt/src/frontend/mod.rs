@@ -1,51 +1,137 @@
+//! This module contains everything related to the frontend.
+
 pub mod html;
 pub mod render;
-mod virtual_dom;
-
-use std::sync::Arc;
+pub mod context;
+pub mod store;
+pub mod signal;
+pub mod hooks;
+pub mod error_boundary;
+pub mod portal;
+pub mod suspense;
+pub mod island;
+pub mod snapshot;
+pub(crate) mod virtual_dom;
+mod scheduler;
+
+use futures_util::{Stream, StreamExt};
+
+use std::marker::PhantomData;
+use std::future::Future;
 use std::any::Any;
+use std::pin::pin;
 use std::rc::Rc;
 
 use crate::frontend::html::{Children, Html};
 use crate::frontend::render::Renderer;
 use crate::frontend::html::path::Path;
+use crate::frontend::error_boundary::Failure;
 
 
-/// A [`Link`] allows you to callback to a component from anywhere in your codebase, the link can be cloned and will still point
+/// A [`Link`] allows you to send messages to a component from anywhere in your codebase, the link can be cloned and will still point
 /// to the same component.
-#[derive(Clone)]
-pub struct Link {
+///
+/// The link is typed by the component it points to, so only that component's [`Message`](Component::Message) type can be sent through it.
+pub struct Link<C: Component> {
     renderer: Renderer,
     scope: Path,
+    _marker: PhantomData<fn() -> C>,
 }
 
-impl Link {
-    /// Create a new link.
-    pub fn new(renderer: Renderer, scope: Path) -> Link {
+impl<C: Component> Clone for Link<C> {
+    fn clone(&self) -> Link<C> {
+        Link::new(self.renderer.clone(), self.scope.clone())
+    }
+}
+
+impl<C: Component> Link<C> {
+    /// Create a new link to the component at `scope`.
+    pub fn new(renderer: Renderer, scope: Path) -> Link<C> {
         Link {
             renderer,
             scope,
+            _marker: PhantomData,
         }
     }
 
-    /// Call the callback attached to the link.
-    pub fn callback<T: Component>(&self, message: T::Message) {
-        let message: Arc<dyn Any + Send + Sync> = Arc::new(message);
-        let component = self.renderer.get(&self.scope);
+    /// Send a message to the component.
+    ///
+    /// The message is queued and delivered after any message that is currently being handled,
+    /// so it is safe to call this from within a [`callback`](Component::callback) or [`view`](Component::view).
+    pub fn send_message(&self, message: C::Message) {
+        self.renderer.send(self.scope.clone(), Rc::new(message));
+    }
 
-        component.lock().base_callback(&message, self.clone());
+    /// Run a future and send its output to the component once it resolves.
+    ///
+    /// The future is cancelled if the component unmounts before it resolves.
+    pub fn send_future(&self, future: impl Future<Output = C::Message> + 'static) {
+        let link = self.clone();
 
-        self.renderer.render();
+        self.renderer.spawn(self.scope.clone(), async move {
+            link.send_message(future.await);
+        });
+    }
+
+    /// Send every item of a stream to the component as it arrives.
+    ///
+    /// The stream is cancelled if the component unmounts before it ends.
+    pub fn send_stream(&self, stream: impl Stream<Item = C::Message> + 'static) {
+        let link = self.clone();
+
+        self.renderer.spawn(self.scope.clone(), async move {
+            let mut stream = pin!(stream);
+
+            while let Some(message) = stream.next().await {
+                link.send_message(message);
+            }
+        });
+    }
+
+    /// Provide a context of type `T` to every descendant of the component.
+    ///
+    /// Descendants that consume the context with [`use_context`](context::use_context) are re-rendered if the value changed.
+    pub fn provide<T: PartialEq + 'static>(&self, value: T) {
+        self.renderer.provide_context(self.scope.clone(), value);
+    }
+
+    /// Fail the component, the closest [`ErrorBoundary`](error_boundary::ErrorBoundary) above it renders its fallback instead.
+    ///
+    /// ## Panics
+    /// Panics if there is no error boundary above the component.
+    pub fn fail(&self, error: impl std::fmt::Display) {
+        self.renderer.fail(Failure::new(error.to_string(), self.scope.clone()));
+    }
+
+    /// Create a closure that maps its argument into a message and sends it to the component.
+    ///
+    /// This is useful for service responses and timers.
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # use stunt::prelude::*;
+    /// # pub enum Message { Response(usize) }
+    /// # fn example<C: Component<Message = Message>>(link: Link<C>) {
+    /// let callback = link.callback(|response| Message::Response(response));
+    ///
+    /// callback(44);
+    /// # }
+    /// ```
+    pub fn callback<T>(&self, f: impl Fn(T) -> C::Message + 'static) -> impl Fn(T) + 'static {
+        let link = self.clone();
+
+        move |value| link.send_message(f(value))
     }
 }
 
 /// A component is one of the basic building blocks within stunt. A component can pass messages
 /// to its callback and receive properties from the parent.
 ///
-/// You can implement component on virtually any type.
-pub trait Component: Send + Sync + Sized + 'static {
+/// You can implement component on virtually any type. Components are never shared between threads, so
+/// they can own `Rc`s, closures and DOM handles directly.
+pub trait Component: Sized + 'static {
     /// The message type will be passed to the [`callback`](Component::callback).
-    type Message: Any + Send + Sync + 'static;
+    type Message: Any + 'static;
 
     /// The [`Properties`] will be passed down from the parent to the [`view`](Component::view).
     type Properties: Properties + Buildable;
@@ -54,13 +140,13 @@ pub trait Component: Send + Sync + Sized + 'static {
     fn create() -> Self
     where Self: Sized;
 
-    /// Called once on the first render.
+    /// Called once after the first render of the component.
     #[allow(unused_variables)]
-    fn once(&mut self, link: Link) {}
+    fn once(&mut self, link: Link<Self>) {}
 
     /// Recieve a callback. Callbacks can safely mutate the state of the component.
     #[allow(unused_variables)]
-    fn callback(&mut self, callback: &Self::Message, link: Link) {}
+    fn callback(&mut self, callback: &Self::Message, link: Link<Self>) {}
 
     /// The view describes the layout of how your component is to be rendered in the DOM.
     fn view(&self, properties: Self::Properties) -> Html;
@@ -73,16 +159,23 @@ pub trait Component: Send + Sync + Sized + 'static {
 /// ## Warning
 /// This trait is not meant to be used outside the framework.
 pub trait BaseComponent {
+    /// Dyn compatible implementation of once.
+    fn base_once(&mut self, renderer: Renderer, scope: Path);
+
     /// Dyn compatible implementation of a callback.
-    fn base_callback(&mut self, callback: &Arc<dyn Any + Send + Sync>, link: Link);
+    fn base_callback(&mut self, callback: &Rc<dyn Any>, renderer: Renderer, scope: Path);
 
     /// Low-level implementation of a view.
     fn base_view(&self, properties: Rc<dyn Any>) -> Html;
 }
 
 impl<T: Component> BaseComponent for T {
-    fn base_callback(&mut self, callback: &Arc<dyn Any + Send + Sync>, link: Link) {
-        T::callback(self, callback.downcast_ref().expect("invalid callback type"), link)
+    fn base_once(&mut self, renderer: Renderer, scope: Path) {
+        T::once(self, Link::new(renderer, scope))
+    }
+
+    fn base_callback(&mut self, callback: &Rc<dyn Any>, renderer: Renderer, scope: Path) {
+        T::callback(self, callback.downcast_ref().expect("invalid callback type"), Link::new(renderer, scope))
     }
 
     fn base_view(&self, properties: Rc<dyn Any>) -> Html { T::view(self, T::Properties::into_properties(properties)) }
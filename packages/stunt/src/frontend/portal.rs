@@ -0,0 +1,274 @@
+//! Portals render their children into a different DOM node than the rest of the application.
+//!
+//! The children of a [`Portal`] are still part of the component tree, they receive messages, contexts and
+//! lifecycle calls like any other component. Only the DOM is placed elsewhere, inside a container that the
+//! portal appends to its target and that is reconciled separately from the renderer root.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//! use stunt::frontend::portal::Portal;
+//!
+//! pub struct App;
+//!
+//! impl Component for App {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> App { App }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         html! {
+//!             <div style={ "overflow: hidden" }>
+//!                 <Portal target={ String::from("#modal-root") }>
+//!                     <div class={ "modal" }>
+//!                         { "escaped the overflow" }
+//!                     </div>
+//!                 </Portal>
+//!             </div>
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::frontend::virtual_dom::{VirtualKind, VirtualElement, VirtualNode};
+use crate::frontend::html::path::Path;
+use crate::frontend::html::{Children, Html};
+use crate::frontend::render::{Renderer, RenderError};
+use crate::frontend::signal::Effect;
+use crate::frontend::Component;
+
+use stunt_macro::{html, Properties};
+
+use wasm_bindgen::prelude::*;
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+
+/// The DOM of a portal that is mounted into its target.
+struct Mounted {
+    target: String,
+    container: Rc<web_sys::HtmlElement>,
+    previous: VirtualNode,
+    _bindings: Vec<Effect>,
+}
+
+/// The portals of a renderer.
+#[derive(Default)]
+pub(crate) struct Portals {
+    targets: RefCell<HashMap<Path, String>>,
+    rendered: RefCell<Vec<(Path, VirtualNode)>>,
+    mounted: RefCell<HashMap<Path, Mounted>>,
+}
+
+impl Portals {
+    /// Register the portal at `scope`, its children are diverted to `target`.
+    pub(crate) fn register(&self, scope: Path, target: String) {
+        self.targets.borrow_mut().insert(scope, target);
+    }
+
+    /// Take the nodes rendered by the component at `scope` if it is a portal, otherwise give them back.
+    pub(crate) fn divert(&self, scope: &Path, nodes: Vec<VirtualNode>) -> Vec<VirtualNode> {
+        if !self.targets.borrow().contains_key(scope) {
+            return nodes;
+        }
+
+        let vdom = VirtualNode::new(Rc::new(Vec::new()), VirtualKind::Element(VirtualElement::new(String::from("root"), Vec::new(), Rc::new(nodes))), scope.clone());
+
+        self.rendered.borrow_mut().push((scope.clone(), vdom));
+
+        Vec::new()
+    }
+
+    /// Forget the nodes of an earlier render pass that was thrown away.
+    pub(crate) fn clear(&self) {
+        self.rendered.borrow_mut().clear();
+    }
+
+    /// Reconcile every portal rendered in the last render pass against its own container, returns the errors of the
+    /// portals that failed. A failing portal doesnt keep the others from being reconciled.
+    pub(crate) fn reconcile(&self, renderer: &Renderer, document: &web_sys::Document) -> Vec<RenderError> {
+        let rendered = self.rendered.take();
+        let targets = self.targets.borrow().clone();
+
+        rendered.into_iter()
+            .filter_map(|(scope, vdom)| {
+                let target = targets.get(&scope)?;
+
+                self.reconcile_portal(renderer, scope, target, vdom, document).err()
+            })
+            .collect()
+    }
+
+    fn reconcile_portal(&self, renderer: &Renderer, scope: Path, target: &str, vdom: VirtualNode, document: &web_sys::Document) -> Result<(), RenderError> {
+        let mut mounted = self.mounted.borrow_mut();
+
+        let portal = match mounted.entry(scope) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Mounted {
+                target: String::new(),
+                container: Rc::new(Portals::container(document)?),
+                previous: VirtualNode::default(),
+                _bindings: Vec::new(),
+            }),
+        };
+
+        // The container is moved when the target changes and put back if something replaced the content of the target.
+        if portal.target != target || !portal.container.is_connected() {
+            Portals::target(target, document)?.append_child(&portal.container)?;
+
+            portal.target = target.to_string();
+        }
+
+        if let Err(err) = vdom.reconcile(renderer.clone(), &portal.container, &portal.previous, document) {
+            // The container is rebuilt by the next render, like the root of the renderer.
+            renderer.retire([std::mem::take(&mut portal.previous), vdom]);
+
+            return Err(err);
+        }
+
+        let mut bindings = Vec::new();
+
+        vdom.bind(renderer, &mut bindings);
+
+        portal.previous = vdom;
+        portal._bindings = bindings;
+
+        Ok(())
+    }
+
+    /// Remove the container of an unmounted portal from the DOM.
+    pub(crate) fn remove(&self, scope: &Path) {
+        self.targets.borrow_mut().remove(scope);
+
+        if let Some(portal) = self.mounted.borrow_mut().remove(scope) {
+            portal.container.remove();
+        }
+    }
+
+    fn container(document: &web_sys::Document) -> Result<web_sys::HtmlElement, RenderError> {
+        let container = document.create_element("div")?;
+
+        container.set_attribute("data-stunt-portal", "")?;
+
+        container.dyn_into::<web_sys::HtmlElement>()
+            .map_err(|_| RenderError::Cast { expected: "HtmlElement" })
+    }
+
+    fn target(selector: &str, document: &web_sys::Document) -> Result<web_sys::Element, RenderError> {
+        document.query_selector(selector)?
+            .ok_or_else(|| RenderError::NodeNotFound(format!("no portal target matches {}", selector)))
+    }
+}
+
+/// The properties of a [`Portal`].
+#[derive(Properties, Clone)]
+pub struct PortalProperties {
+    /// A css selector for the element the children are rendered into, e.g. `body` or `#modal-root`.
+    pub target: String,
+
+    /// The children rendered into the target.
+    pub children: Children,
+}
+
+/// A component that renders its children into the element matched by its target.
+pub struct Portal;
+
+impl Component for Portal {
+    type Message = ();
+    type Properties = PortalProperties;
+
+    fn create() -> Portal { Portal }
+
+    fn view(&self, properties: PortalProperties) -> Html {
+        if let Some((renderer, scope)) = Renderer::current() {
+            renderer.register_portal(scope, properties.target);
+        }
+
+        html! {
+            { properties.children }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Properties, Clone)]
+    struct AppProperties {
+        target: String,
+        open: bool,
+    }
+
+    struct App;
+
+    impl Component for App {
+        type Message = ();
+        type Properties = AppProperties;
+
+        fn create() -> App { App }
+
+        fn view(&self, properties: AppProperties) -> Html {
+            let portal = match properties.open {
+                true => html! {
+                    <Portal target={ properties.target }>
+                        <p>
+                            { "modal" }
+                        </p>
+                    </Portal>
+                },
+                false => Html::default(),
+            };
+
+            html! {
+                <main>
+                    { portal }
+                </main>
+            }
+        }
+    }
+
+    fn mount(target: &str, open: bool) -> Renderer {
+        let renderer = Renderer::detached(App);
+
+        renderer.set_root_props(AppProperties { target: target.to_string(), open });
+        renderer.render();
+
+        renderer
+    }
+
+    fn targets(renderer: &Renderer) -> Vec<String> {
+        renderer.portals().targets.borrow().values().cloned().collect()
+    }
+
+    #[test]
+    fn children_are_diverted_to_the_target() {
+        let renderer = mount("#modal", true);
+
+        assert_eq!(renderer.tree().inner_html(), "<main></main>");
+        assert_eq!(targets(&renderer), vec!["#modal"]);
+    }
+
+    #[test]
+    fn the_target_follows_the_properties() {
+        let renderer = mount("#modal", true);
+
+        renderer.set_props(AppProperties { target: String::from("body"), open: true });
+
+        assert_eq!(targets(&renderer), vec!["body"]);
+        assert_eq!(renderer.tree().inner_html(), "<main></main>");
+    }
+
+    #[test]
+    fn unmounted_portals_are_removed() {
+        let renderer = mount("#modal", true);
+
+        renderer.set_props(AppProperties { target: String::from("#modal"), open: false });
+
+        assert!(targets(&renderer).is_empty());
+        assert!(renderer.portals().mounted.borrow().is_empty());
+    }
+}
@@ -23,31 +23,134 @@
 //!     Renderer::new::<App>().render();
 //! }
 //! ```
+//!
+//! A renderer can also be mounted on any element of an existing page with [`Renderer::mount`], its root properties
+//! updated with [`Renderer::set_props`] and torn down again with [`Renderer::unmount`].
+//!
+//! Failures to update the DOM are passed to the handler installed with [`Renderer::on_error`] as a [`RenderError`].
 
 use crate::frontend::html::path::Path;
 use crate::frontend::virtual_dom::{VirtualKind, VirtualElement, VirtualNode};
-use crate::frontend::{Component, BaseComponent};
+use crate::frontend::scheduler::{Render, Scheduler, Task};
+use crate::frontend::context::Contexts;
+use crate::frontend::error_boundary::{Boundaries, Failure};
+use crate::frontend::portal::Portals;
+use crate::frontend::suspense::{Deferred, Deferreds};
+use crate::frontend::signal::Effect;
+use crate::frontend::{Component, BaseComponent, Properties};
+use crate::frontend::html::Html;
+use crate::backend::transfer;
 
 use wasm_bindgen::prelude::*;
-use spin::Mutex;
 
-use std::collections::HashMap;
+use futures_util::future::{Abortable, LocalBoxFuture, FutureExt};
+
+use std::collections::{HashMap, HashSet};
+use std::panic::{self, AssertUnwindSafe};
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Wake, Waker};
+use std::any::{Any, TypeId};
 use std::sync::Arc;
-use std::any::Any;
 use std::rc::Rc;
 
 
+/// A shared handle to a mounted component.
+pub(crate) type ComponentRef = Rc<RefCell<dyn BaseComponent>>;
+
+/// A handler for the errors of a renderer.
+pub(crate) type ErrorHandler = Rc<dyn Fn(RenderError)>;
+
+/// An error that prevented the DOM from being updated.
+///
+/// A failed render may have updated part of the DOM already, the next render rebuilds the DOM from scratch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderError {
+    /// A DOM node the renderer depends on doesnt exist, e.g. the target of a portal.
+    NodeNotFound(String),
+
+    /// A DOM node isnt of the expected type.
+    Cast {
+        /// The type the node was expected to be.
+        expected: &'static str,
+    },
+
+    /// A DOM operation threw an exception.
+    Js(String),
+
+    /// An [island](crate::frontend::island) couldnt be hydrated, e.g. because it isnt registered.
+    Island(String),
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            RenderError::NodeNotFound(message) => write!(f, "node not found: {}", message),
+            RenderError::Cast { expected } => write!(f, "failed to cast node to {}", expected),
+            RenderError::Js(message) => write!(f, "javascript exception: {}", message),
+            RenderError::Island(message) => write!(f, "failed to hydrate island: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+/// The error handler of a renderer until another one is installed, it logs the error to the console.
+pub(crate) fn log_error(err: RenderError) {
+    web_sys::console::error_1(&format!("failed to render: {}", err).into());
+}
+
+impl From<JsValue> for RenderError {
+    fn from(value: JsValue) -> RenderError {
+        RenderError::Js(value.as_string().unwrap_or_else(|| format!("{:?}", value)))
+    }
+}
+
+thread_local! {
+    /// The renderer and path of the components whose [`view`](Component::view) is currently running.
+    static VIEWING: RefCell<Vec<(Renderer, Path)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Remembers if a future of a detached renderer was woken, it can make progress when it is polled again.
+#[derive(Default)]
+struct Woken(AtomicBool);
+
+impl Wake for Woken {
+    fn wake(self: Arc<Woken>) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
 /// Represents the renderer.
+///
+/// The renderer and everything it owns lives on a single thread, components are therefore free to
+/// hold `Rc`s, closures and DOM handles.
 #[derive(Clone)]
 pub struct Renderer {
-    components: Arc<Mutex<HashMap<Path, Arc<Mutex<dyn BaseComponent + Send + Sync>>>>>,
-    previous: Arc<Mutex<VirtualNode>>,
-    root: Rc<web_sys::HtmlElement>,
+    components: Rc<RefCell<HashMap<Path, ComponentRef>>>,
+    rendered: Rc<RefCell<HashSet<Path>>>,
+    views: Rc<RefCell<HashMap<Path, Html>>>,
+    dirty: Rc<RefCell<Option<HashSet<Path>>>>,
+    contexts: Rc<Contexts>,
+    boundaries: Rc<Boundaries>,
+    portals: Rc<Portals>,
+    deferreds: Rc<Deferreds>,
+    previous: Rc<RefCell<VirtualNode>>,
+    stale: Rc<RefCell<Vec<VirtualNode>>>,
+    bindings: Rc<RefCell<Vec<Effect>>>,
+    properties: Rc<RefCell<Rc<dyn Any>>>,
+    root_properties: TypeId,
+    scheduler: Rc<Scheduler>,
+    hydrating: Rc<Cell<bool>>,
+    local: Rc<RefCell<Vec<LocalBoxFuture<'static, ()>>>>,
+    on_error: Rc<RefCell<ErrorHandler>>,
+    root: Option<Rc<web_sys::HtmlElement>>,
 }
 
 impl Renderer {
     /// Create a new render instance with the body as root element.
-    pub fn new<T: Component + Send + Sync + 'static>() -> Renderer {
+    pub fn new<T: Component>() -> Renderer {
         let window = web_sys::window().expect("no global window exists");
         let document = window.document().expect("should have a document on window");
         let body = document.body().expect("document should have a body");
@@ -56,18 +159,12 @@ impl Renderer {
     }
 
     /// Create a new render instance with a root element.
-    pub fn new_with_root<T: Component + Send + Sync + 'static>(root: web_sys::HtmlElement) -> Renderer {
-        let component = Arc::new(Mutex::new(T::create()));
-
-        Renderer {
-            components: Arc::new(Mutex::new(HashMap::from([(Path::new(), component as Arc<Mutex<dyn BaseComponent + Send + Sync>>)]))),
-            previous: Arc::new(Mutex::new(VirtualNode::default())),
-            root: Rc::new(root),
-        }
+    pub fn new_with_root<T: Component>(root: web_sys::HtmlElement) -> Renderer {
+        Renderer::use_prepared_comp_with_root(T::create(), root)
     }
 
     /// Create a new render instance with the body as root element and using the passed component.
-    pub fn use_prepared_comp(component: impl Component + Send + Sync + 'static) -> Renderer {
+    pub fn use_prepared_comp(component: impl Component) -> Renderer {
         let window = web_sys::window().expect("no global window exists");
         let document = window.document().expect("should have a document on window");
         let body = document.body().expect("document should have a body");
@@ -76,62 +173,716 @@ impl Renderer {
     }
 
     /// Create a new render instance with a root element.
-    pub fn use_prepared_comp_with_root(component: impl Component + Send + Sync + 'static, root: web_sys::HtmlElement) -> Renderer {
+    pub fn use_prepared_comp_with_root(component: impl Component, root: web_sys::HtmlElement) -> Renderer {
+        Renderer::with_root(component, Some(root))
+    }
+
+    /// Create a render instance without a root element, its views are rendered without touching the DOM.
+    pub(crate) fn detached(component: impl Component) -> Renderer {
+        Renderer::with_root(component, None)
+    }
+
+    fn with_root<T: Component>(component: T, root: Option<web_sys::HtmlElement>) -> Renderer {
+        let scheduler = Scheduler::default();
+
+        scheduler.mount(Path::new());
+
         Renderer {
-            components: Arc::new(Mutex::new(HashMap::from([(Path::new(), Arc::new(Mutex::new(component)) as Arc<Mutex<dyn BaseComponent + Send + Sync>>)]))),
-            previous: Arc::new(Mutex::new(VirtualNode::default())),
-            root: Rc::new(root),
+            components: Rc::new(RefCell::new(HashMap::from([(Path::new(), Rc::new(RefCell::new(component)) as ComponentRef)]))),
+            rendered: Rc::new(RefCell::new(HashSet::new())),
+            views: Rc::new(RefCell::new(HashMap::new())),
+            dirty: Rc::new(RefCell::new(None)),
+            contexts: Rc::new(Contexts::default()),
+            boundaries: Rc::new(Boundaries::default()),
+            portals: Rc::new(Portals::default()),
+            deferreds: Rc::new(Deferreds::default()),
+            previous: Rc::new(RefCell::new(VirtualNode::default())),
+            stale: Rc::new(RefCell::new(Vec::new())),
+            bindings: Rc::new(RefCell::new(Vec::new())),
+            properties: Rc::new(RefCell::new(Rc::new(()))),
+            root_properties: TypeId::of::<T::Properties>(),
+            scheduler: Rc::new(scheduler),
+            hydrating: Rc::new(Cell::new(false)),
+            local: Rc::new(RefCell::new(Vec::new())),
+            on_error: Rc::new(RefCell::new(Rc::new(log_error))),
+            root: root.map(Rc::new),
         }
     }
 
-    pub(crate) fn get_element_by_path(&self, path: &Path, document: &web_sys::Document) -> Result<web_sys::HtmlElement, JsValue> {
-        let node = document.evaluate(&format!(".{}", path), &self.root)?
-            .iterate_next()?
-            .ok_or(JsValue::from_str("failed to get node"))?;
+    /// Mount a component with properties on the first element matching a css selector and render it.
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # use stunt::prelude::*;
+    /// #[derive(Properties, Clone)]
+    /// pub struct WidgetProperties {
+    ///     title: String,
+    /// }
+    ///
+    /// pub struct Widget;
+    ///
+    /// impl Component for Widget {
+    ///     type Message = ();
+    ///     type Properties = WidgetProperties;
+    ///
+    ///     fn create() -> Widget { Widget }
+    ///
+    ///     fn view(&self, properties: WidgetProperties) -> Html {
+    ///         html! {
+    ///             <h2>
+    ///                 { properties.title }
+    ///             </h2>
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// let renderer = Renderer::mount::<Widget>("#widget", WidgetProperties { title: String::from("hello") });
+    ///
+    /// renderer.set_props(WidgetProperties { title: String::from("world") });
+    ///
+    /// renderer.unmount();
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if no element matches the selector.
+    pub fn mount<T: Component>(selector: &str, properties: T::Properties) -> Renderer {
+        Renderer::mount_with_error_handler::<T>(selector, properties, log_error)
+    }
 
-        node.dyn_ref::<web_sys::HtmlElement>()
-            .map(|element| element.clone())
-            .ok_or(JsValue::from_str("failed to cast"))
+    /// Mount a component like [`mount`](Renderer::mount), with a handler for the errors of every render including
+    /// the first, see [`on_error`](Renderer::on_error).
+    ///
+    /// ## Panics
+    /// Panics if no element matches the selector.
+    pub fn mount_with_error_handler<T: Component>(selector: &str, properties: T::Properties, handler: impl Fn(RenderError) + 'static) -> Renderer {
+        let window = web_sys::window().expect("no global window exists");
+        let document = window.document().expect("should have a document on window");
+
+        let root = document.query_selector(selector)
+            .ok()
+            .flatten()
+            .and_then(|element| element.dyn_into::<web_sys::HtmlElement>().ok())
+            .unwrap_or_else(|| panic!("no element matches {}", selector));
+
+        let renderer = Renderer::new_with_root::<T>(root).on_error(handler);
+
+        renderer.set_root_props(properties);
+
+        renderer.render();
+
+        renderer
     }
 
-    pub(crate) fn get(&self, path: &Path) -> Arc<Mutex<dyn BaseComponent + Send + Sync>> {
-        self.components.lock()[path].clone()
+    /// Hydrate the html rendered by [`ssr::render_to_string`](crate::ssr::render_to_string) in the body.
+    ///
+    /// The first render adopts the existing DOM instead of recreating it, only the event listeners are attached.
+    /// Nodes that dont match the first render are replaced and reported in debug builds. Nodes left over after the
+    /// application are removed, except scripts, styles, links and templates that the page may have added to the body.
+    ///
+    /// The outputs of the [`Service`](crate::backend::Service) calls made while rendering on the server are read from
+    /// the page, the same calls resolve from them instead of calling the server again.
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # use stunt::prelude::*;
+    /// pub struct App;
+    ///
+    /// impl Component for App {
+    ///     type Message = ();
+    ///     type Properties = ();
+    ///
+    ///     fn create() -> App { App }
+    ///
+    ///     fn view(&self, _: ()) -> Html {
+    ///         html! {
+    ///             <button onclick={ () }>
+    ///                 { "rendered on the server" }
+    ///             </button>
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     Renderer::hydrate::<App>();
+    /// }
+    /// ```
+    pub fn hydrate<T: Component>() -> Renderer {
+        Renderer::hydrate_with_error_handler::<T>(log_error)
     }
 
-    pub(crate) fn get_or_insert(&self, path: &Path, f: impl Fn() -> Arc<Mutex<dyn BaseComponent + Send + Sync>>) -> Arc<Mutex<dyn BaseComponent + Send + Sync>> {
-        let mut components = self.components.lock();
+    /// Hydrate the body like [`hydrate`](Renderer::hydrate), with a handler for the errors of every render including
+    /// the first, see [`on_error`](Renderer::on_error).
+    pub fn hydrate_with_error_handler<T: Component>(handler: impl Fn(RenderError) + 'static) -> Renderer {
+        let window = web_sys::window().expect("no global window exists");
+        let document = window.document().expect("should have a document on window");
 
-        match components.get(path) {
-            Some(component) => component.clone(),
-            None => {
-                components.insert(path.clone(), (f)());
+        transfer::load(&document);
 
-                components[&path].clone()
-            },
+        let renderer = Renderer::new::<T>().on_error(handler);
+
+        renderer.hydrating.set(true);
+
+        renderer.render();
+
+        renderer
+    }
+
+    /// Hydrate the html of a component with properties rendered into `root`.
+    pub(crate) fn hydrate_with_root<T: Component>(root: web_sys::HtmlElement, properties: T::Properties, handler: ErrorHandler) -> Renderer {
+        let renderer = Renderer::new_with_root::<T>(root).on_error(move |err| handler(err));
+
+        renderer.set_root_props(properties);
+
+        renderer.hydrating.set(true);
+
+        renderer.render();
+
+        renderer
+    }
+
+    /// Replace the properties of the root component and re-render it.
+    ///
+    /// ## Panics
+    /// Panics if `P` isnt the properties type of the root component.
+    pub fn set_props<P: Properties + 'static>(&self, properties: P) {
+        assert!(TypeId::of::<P>() == self.root_properties, "the properties do not belong to the root component");
+
+        *self.properties.borrow_mut() = Rc::new(properties);
+
+        self.render();
+    }
+
+    pub(crate) fn set_root_props<P: Properties + 'static>(&self, properties: P) {
+        *self.properties.borrow_mut() = Rc::new(properties);
+    }
+
+    /// Install a handler for the errors that prevent the DOM from being updated, they are logged to the console
+    /// otherwise.
+    ///
+    /// The handler only sees the renders after it is installed,
+    /// [`mount_with_error_handler`](Renderer::mount_with_error_handler) and
+    /// [`hydrate_with_error_handler`](Renderer::hydrate_with_error_handler) install it before the first render.
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # use stunt::prelude::*;
+    /// # pub struct App;
+    /// #
+    /// # impl Component for App {
+    /// #     type Message = ();
+    /// #     type Properties = ();
+    /// #
+    /// #     fn create() -> App { App }
+    /// #
+    /// #     fn view(&self, _: ()) -> Html {
+    /// #         html! { <div></div> }
+    /// #     }
+    /// # }
+    /// #
+    /// # fn main() {
+    /// Renderer::new::<App>()
+    ///     .on_error(|err| web_sys::console::warn_1(&format!("render failed: {}", err).into()))
+    ///     .render();
+    /// # }
+    /// ```
+    pub fn on_error(self, handler: impl Fn(RenderError) + 'static) -> Renderer {
+        *self.on_error.borrow_mut() = Rc::new(handler);
+
+        self
+    }
+
+    /// Keep the trees of a render that failed halfway until the next render, the DOM still holds their listeners.
+    ///
+    /// A failed render leaves a DOM that matches neither tree, it is rebuilt from scratch by the next render.
+    pub(crate) fn retire(&self, trees: impl IntoIterator<Item = VirtualNode>) {
+        self.stale.borrow_mut().extend(trees);
+    }
+
+    /// Pass an error to the error handler.
+    pub(crate) fn report(&self, err: RenderError) {
+        let handler = self.on_error.borrow().clone();
+
+        handler(err);
+    }
+
+    /// Tear the application down.
+    ///
+    /// Every component is dropped, their futures are aborted, the event listeners are removed and the root is cleared.
+    /// Messages and renders after unmounting are ignored.
+    pub fn unmount(&self) {
+        self.scheduler.close();
+
+        self.rendered.borrow_mut().clear();
+
+        self.unmount_stale();
+
+        self.bindings.borrow_mut().clear();
+
+        if let Some(root) = &self.root {
+            root.set_inner_html("");
+        }
+
+        // The previous tree owns the event listeners.
+        *self.previous.borrow_mut() = VirtualNode::default();
+
+        self.stale.borrow_mut().clear();
+    }
+
+    /// Returns true if `other` is a clone of this renderer.
+    pub(crate) fn is(&self, other: &Renderer) -> bool {
+        Rc::ptr_eq(&self.scheduler, &other.scheduler)
+    }
+
+    /// Returns true if the renderer renders without a DOM, e.g. on the server.
+    pub(crate) fn is_detached(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub(crate) fn get(&self, path: &Path) -> Option<ComponentRef> {
+        self.components.borrow().get(path).cloned()
+    }
+
+    pub(crate) fn get_or_insert(&self, path: &Path, f: impl Fn() -> ComponentRef) -> ComponentRef {
+        self.rendered.borrow_mut().insert(path.clone());
+
+        self.components.borrow_mut()
+            .entry(path.clone())
+            .or_insert_with(|| {
+                self.scheduler.mount(path.clone());
+
+                f()
+            })
+            .clone()
+    }
+
+    /// Run the view of a component, the component is considered the current scope until the view returns.
+    ///
+    /// A panicking view renders nothing and fails the component. During a partial render, components
+    /// outside of the dirty scopes return the html of their last view instead.
+    pub(crate) fn view(&self, component: &ComponentRef, scope: &Path, properties: Rc<dyn Any>) -> Html {
+        if let Some(html) = self.last_view(scope) {
+            return html;
+        }
+
+        VIEWING.with_borrow_mut(|viewing| viewing.push((self.clone(), scope.clone())));
+
+        let html = panic::catch_unwind(AssertUnwindSafe(|| component.borrow().base_view(properties)));
+
+        VIEWING.with_borrow_mut(|viewing| viewing.pop());
+
+        let html = html.unwrap_or_else(|payload| {
+            self.fail_with_panic(scope.clone(), payload);
+
+            Html::default()
+        });
+
+        self.views.borrow_mut().insert(scope.clone(), html.clone());
+
+        html
+    }
+
+    /// The html of the last view of the component at `scope`, if the current render is partial and
+    /// neither the component nor any of its ancestors is dirty. The properties of a component only
+    /// change when the view of an ancestor runs, so its last view is still up to date.
+    fn last_view(&self, scope: &Path) -> Option<Html> {
+        let dirty = self.dirty.borrow();
+        let dirty = dirty.as_ref()?;
+
+        if scope.ancestors().any(|ancestor| dirty.contains(&ancestor)) {
+            return None;
+        }
+
+        self.views.borrow().get(scope).cloned()
+    }
+
+    /// Run `f` on behalf of the component at `scope`, a panic fails the component.
+    fn guard(&self, scope: &Path, f: impl FnOnce()) {
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) {
+            self.fail_with_panic(scope.clone(), payload);
+        }
+    }
+
+    /// Hand a failure to the closest error boundary, which is re-rendered with its fallback.
+    ///
+    /// Failing within a view doesnt mark the boundary, the render is repeated once every view returned.
+    ///
+    /// ## Panics
+    /// Panics if no error boundary is able to catch the failure.
+    pub(crate) fn fail(&self, failure: Failure) {
+        match self.boundaries.catch(failure) {
+            Ok(_) => self.rerender_boundary(),
+            Err(failure) => panic!("uncaught failure in {}: {}", failure.scope(), failure),
+        }
+    }
+
+    fn fail_with_panic(&self, scope: Path, payload: Box<dyn Any + Send>) {
+        match self.boundaries.catch(Failure::from_panic(payload.as_ref(), scope)) {
+            Ok(_) => self.rerender_boundary(),
+            Err(_) => panic::resume_unwind(payload),
+        }
+    }
+
+    /// Render the boundary that caught a failure, unless a view failed and the boundary is rendered by the
+    /// current render already.
+    fn rerender_boundary(&self) {
+        if Renderer::current().is_none() {
+            self.render();
+        }
+    }
+
+    pub(crate) fn register_boundary(&self, scope: Path) -> Option<Failure> {
+        self.boundaries.register(scope)
+    }
+
+    pub(crate) fn reset_boundary(&self, scope: &Path) {
+        self.boundaries.reset(scope);
+    }
+
+    pub(crate) fn register_portal(&self, scope: Path, target: String) {
+        self.portals.register(scope, target);
+    }
+
+    /// Take the nodes rendered by the component at `scope` if it is a portal, they are reconciled against the
+    /// portal target instead of the root.
+    pub(crate) fn divert(&self, scope: &Path, nodes: Vec<VirtualNode>) -> Vec<VirtualNode> {
+        self.portals.divert(scope, nodes)
+    }
+
+    /// Defer the content of the suspense at `scope` if the html is streamed, returns the id of its placeholder.
+    pub(crate) fn defer(&self, scope: Path, content: &Deferred) -> Option<usize> {
+        self.deferreds.defer(scope, content)
+    }
+
+    pub(crate) fn deferreds(&self) -> &Deferreds {
+        &self.deferreds
+    }
+
+    #[cfg(test)]
+    pub(crate) fn portals(&self) -> &Portals {
+        &self.portals
+    }
+
+    /// The renderer and path of the component whose view is currently running.
+    pub(crate) fn current() -> Option<(Renderer, Path)> {
+        VIEWING.with_borrow(|viewing| viewing.last().cloned())
+    }
+
+    /// Provide a context at `scope` and schedule a render if the value changed and is consumed.
+    ///
+    /// Providing from within a view doesnt schedule anything, the consumers are descendants of the
+    /// provider and are rendered after it in the same render.
+    pub(crate) fn provide_context<T: PartialEq + 'static>(&self, scope: Path, value: T) {
+        let consumed = self.contexts.provide(scope, value);
+
+        if consumed && Renderer::current().is_none() {
+            self.render();
         }
     }
 
+    pub(crate) fn consume_context<T: Clone + 'static>(&self, scope: &Path) -> Option<T> {
+        self.contexts.consume(scope)
+    }
+
+    /// Queue a message for the component at `scope`. The message is delivered once every
+    /// message sent before it has been handled, never while another component callback is running.
+    pub(crate) fn send(&self, scope: Path, message: Rc<dyn Any>) {
+        self.scheduler.push(Task { scope, message });
+
+        self.run();
+    }
+
+    /// Spawn a future on behalf of the component at `scope`. The future is aborted if the
+    /// component unmounts before it completes.
+    pub(crate) fn spawn(&self, scope: Path, future: impl Future<Output = ()> + 'static) {
+        let (id, registration) = self.scheduler.register_future(scope.clone());
+        let scheduler = self.scheduler.clone();
+
+        let future = async move {
+            if Abortable::new(future, registration).await.is_ok() {
+                scheduler.complete_future(&scope, id);
+            }
+        };
+
+        // Without a DOM there is no event loop either, the futures are kept until they are polled.
+        match self.root {
+            Some(_) => wasm_bindgen_futures::spawn_local(future),
+            None => self.local.borrow_mut().push(future.boxed_local()),
+        }
+    }
+
+    /// Poll the futures of a detached renderer once, returns true if polling them again can make progress, i.e. if
+    /// any of them completed, was woken or spawned another future.
+    pub(crate) fn poll_local(&self) -> bool {
+        let woken = Arc::new(Woken::default());
+        let waker = Waker::from(woken.clone());
+        let mut context = Context::from_waker(&waker);
+
+        let futures = self.local.take();
+        let count = futures.len();
+
+        let pending = futures.into_iter()
+            .filter_map(|mut future| future.poll_unpin(&mut context).is_pending().then_some(future))
+            .collect::<Vec<LocalBoxFuture<'static, ()>>>();
+
+        let completed = pending.len() < count;
+        let spawned = !self.local.borrow().is_empty();
+
+        // Futures spawned while polling were pushed in the meantime.
+        self.local.borrow_mut().splice(0..0, pending);
+
+        completed || spawned || woken.0.load(Ordering::Relaxed)
+    }
+
+    /// The virtual tree of the last render.
+    pub(crate) fn tree(&self) -> VirtualNode {
+        self.previous.borrow().clone()
+    }
+
     /// Render the application.
+    ///
+    /// If the renderer is busy handling a callback or rendering, the render is deferred until
+    /// the pending messages have been handled.
     pub fn render(&self) {
-        let root = self.get(&Path::new());
-        let lock = root.lock();
+        self.scheduler.schedule_render();
+
+        self.run();
+    }
+
+    /// Render the components at `scopes` and their descendants, the views of every other component arent run.
+    ///
+    /// Like [`render`](Renderer::render), the render is deferred while the renderer is busy.
+    pub(crate) fn render_scopes(&self, scopes: impl IntoIterator<Item = Path>) {
+        for scope in scopes {
+            self.scheduler.schedule_partial(scope);
+        }
+
+        self.run();
+    }
+
+    fn run(&self) {
+        let Some(_guard) = self.scheduler.acquire() else { return };
+
+        loop {
+            while let Some(Task { scope, message }) = self.scheduler.pop() {
+                if let Some(component) = self.get(&scope) {
+                    self.guard(&scope, || component.borrow_mut().base_callback(&message, self.clone(), scope.clone()));
+
+                    self.scheduler.schedule_render();
+                }
+            }
 
-        let render = lock.base_view(Rc::new(()) as Rc<dyn Any>).render(self.clone(), Path::new());
+            let Some(render) = self.scheduler.take_render() else { break };
 
-        let vdom = VirtualNode::new(Arc::new(Vec::new()), VirtualKind::Element(VirtualElement::new(String::from("root"), String::new(), Arc::new(render))), Path::new());
+            *self.dirty.borrow_mut() = match render {
+                Render::Full => None,
+                Render::Partial(dirty) => Some(dirty),
+            };
 
-        let mut previous = self.previous.lock();
+            self.reconcile();
+
+            *self.dirty.borrow_mut() = None;
+
+            for scope in self.scheduler.take_mounted() {
+                if let Some(component) = self.get(&scope) {
+                    self.guard(&scope, || component.borrow_mut().base_once(self.clone(), scope.clone()));
+                }
+            }
+        }
+    }
+
+    /// Drop every component that wasnt rendered in the last render and abort its futures.
+    fn unmount_stale(&self) {
+        let rendered = self.rendered.take();
+
+        let stale = self.components.borrow()
+            .keys()
+            .filter(|path| !rendered.contains(path))
+            .cloned()
+            .collect::<Vec<Path>>();
+
+        for path in stale {
+            let component = self.components.borrow_mut().remove(&path);
+
+            self.views.borrow_mut().remove(&path);
+
+            self.scheduler.cancel_futures(&path);
+
+            self.contexts.remove(&path);
+
+            self.boundaries.remove(&path);
+
+            self.portals.remove(&path);
+
+            drop(component);
+        }
+    }
+
+    /// Run the views of every component and return the virtual tree they render.
+    pub(crate) fn view_tree(&self) -> VirtualNode {
+        let root = self.get(&Path::new()).expect("the root component is always mounted");
+
+        self.boundaries.take_caught();
+
+        // A failure caught during the views changes what its boundary renders, so the views are run again
+        // until no boundary catches anything new.
+        let render = loop {
+            self.portals.clear();
+
+            self.rendered.borrow_mut().insert(Path::new());
+
+            let properties = self.properties.borrow().clone();
+
+            let render = self.view(&root, &Path::new(), properties).render(self.clone(), Path::new());
+
+            self.unmount_stale();
+
+            if !self.boundaries.take_caught() {
+                break render;
+            }
+
+            // The boundary that caught the failure may be outside of the dirty scopes.
+            *self.dirty.borrow_mut() = None;
+        };
+
+        Renderer::root_node(render)
+    }
+
+    /// Render html outside of the view of a component, the components in it are mounted below `path`.
+    pub(crate) fn render_html(&self, html: Html, path: Path) -> VirtualNode {
+        Renderer::root_node(html.render(self.clone(), path))
+    }
+
+    fn root_node(render: Vec<VirtualNode>) -> VirtualNode {
+        VirtualNode::new(Rc::new(Vec::new()), VirtualKind::Element(VirtualElement::new(String::from("root"), Vec::new(), Rc::new(render))), Path::new())
+    }
+
+    fn reconcile(&self) {
+        let vdom = self.view_tree();
+
+        let Some(root) = &self.root else {
+            *self.previous.borrow_mut() = vdom;
+
+            return;
+        };
+
+        // The previous tree isnt borrowed while patching, so the error handler is free to use the renderer.
+        let previous = self.previous.borrow().clone();
+
+        // The trees of a failed render are dropped once the DOM that still refers to them is rebuilt below.
+        let _stale = self.stale.take();
 
         let window = web_sys::window().expect("no global window exists");
         let document = window.document().expect("should have a document on window");
 
-        match vdom.reconcile(self.clone(), &*previous, Path::new(), &document) {
-            Ok(()) => *previous = vdom,
+        let result = match self.hydrating.replace(false) {
+            true => vdom.hydrate(self.clone(), root, &document),
+            false => vdom.reconcile(self.clone(), root, &previous, &document),
+        };
+
+        match result {
+            Ok(()) => {
+                let mut bindings = Vec::new();
+
+                vdom.bind(self, &mut bindings);
+
+                *self.bindings.borrow_mut() = bindings;
+                *self.previous.borrow_mut() = vdom;
+            },
             Err(err) => {
-                web_sys::console::error_1(&format!("failed to reconcile: {:?}", err).into());
+                self.retire([previous, vdom]);
+
+                *self.previous.borrow_mut() = VirtualNode::default();
+
+                self.report(err);
             },
         }
+
+        for err in self.portals.reconcile(self, &document) {
+            self.report(err);
+        }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    use stunt_macro::Properties;
+
+    #[derive(Properties, Clone)]
+    struct TitleProperties {
+        title: String,
+    }
+
+    struct Title;
+
+    impl Component for Title {
+        type Message = ();
+        type Properties = TitleProperties;
+
+        fn create() -> Title { Title }
+
+        fn view(&self, properties: TitleProperties) -> Html {
+            Html::wrap("h1", Vec::new(), Rc::new(properties.title))
+        }
+    }
+
+    #[test]
+    fn set_props_renders_the_root_with_the_new_properties() {
+        let renderer = Renderer::detached(Title);
+
+        renderer.set_root_props(TitleProperties { title: String::from("first") });
+        renderer.render();
+
+        renderer.set_props(TitleProperties { title: String::from("second") });
+
+        assert_eq!(renderer.tree().inner_html(), "<h1>second</h1>");
+    }
+
+    #[test]
+    #[should_panic(expected = "the properties do not belong to the root component")]
+    fn set_props_rejects_properties_of_another_component() {
+        let renderer = Renderer::detached(Title);
+
+        renderer.set_props(());
+    }
+
+    #[test]
+    fn on_error_receives_the_reported_errors() {
+        let errors = Rc::new(RefCell::new(Vec::new()));
+
+        let renderer = Renderer::detached(Title).on_error({
+            let errors = errors.clone();
+
+            move |err| errors.borrow_mut().push(err.to_string())
+        });
+
+        renderer.report(RenderError::NodeNotFound(String::from("no portal target matches #modal")));
+        renderer.report(RenderError::Cast { expected: "HtmlElement" });
+        renderer.report(RenderError::Js(String::from("NotFoundError")));
+        renderer.report(RenderError::Island(String::from("Counter isnt registered")));
+
+        assert_eq!(*errors.borrow(), vec![
+            "node not found: no portal target matches #modal",
+            "failed to cast node to HtmlElement",
+            "javascript exception: NotFoundError",
+            "failed to hydrate island: Counter isnt registered",
+        ]);
+    }
+
+    #[test]
+    fn on_error_can_use_the_renderer() {
+        let renderer = Renderer::detached(Title);
+
+        renderer.set_root_props(TitleProperties { title: String::from("rendered by the handler") });
+
+        let handler = renderer.clone();
+        let renderer = renderer.on_error(move |_| handler.render());
+
+        renderer.report(RenderError::Js(String::from("failed")));
+
+        assert_eq!(renderer.tree().inner_html(), "<h1>rendered by the handler</h1>");
+    }
+}
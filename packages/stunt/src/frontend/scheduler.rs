@@ -0,0 +1,193 @@
+use crate::frontend::html::path::Path;
+
+use futures_util::future::{AbortHandle, AbortRegistration};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::cell::{Cell, RefCell};
+use std::any::Any;
+use std::rc::Rc;
+
+
+/// A render that has been scheduled.
+pub(crate) enum Render {
+    /// Run the view of every component.
+    Full,
+    /// Only run the views of the components at these scopes and of their descendants.
+    Partial(HashSet<Path>),
+}
+
+/// A message waiting to be delivered to the component at `scope`.
+pub(crate) struct Task {
+    pub(crate) scope: Path,
+    pub(crate) message: Rc<dyn Any>,
+}
+
+/// The scheduler defers messages and renders so that nothing runs while a component is
+/// borrowed. Messages are delivered in the order they were sent, and pending renders are
+/// coalesced into a single render once the queue has been drained.
+#[derive(Default)]
+pub(crate) struct Scheduler {
+    queue: RefCell<VecDeque<Task>>,
+    mounted: RefCell<Vec<Path>>,
+    futures: RefCell<HashMap<Path, HashMap<u64, AbortHandle>>>,
+    next_future: Cell<u64>,
+    render: Cell<bool>,
+    dirty: RefCell<HashSet<Path>>,
+    running: Cell<bool>,
+    closed: Cell<bool>,
+}
+
+impl Scheduler {
+    pub(crate) fn push(&self, task: Task) {
+        if self.closed.get() {
+            return;
+        }
+
+        self.queue.borrow_mut().push_back(task);
+    }
+
+    pub(crate) fn pop(&self) -> Option<Task> {
+        self.queue.borrow_mut().pop_front()
+    }
+
+    /// Remember a newly created component, its [`once`](crate::frontend::Component::once) is
+    /// called after the render that created it.
+    pub(crate) fn mount(&self, scope: Path) {
+        self.mounted.borrow_mut().push(scope);
+    }
+
+    pub(crate) fn take_mounted(&self) -> Vec<Path> {
+        self.mounted.take()
+    }
+
+    /// Register a future spawned on behalf of the component at `scope`, the future is aborted
+    /// through the returned registration if the component unmounts before it completes.
+    pub(crate) fn register_future(&self, scope: Path) -> (u64, AbortRegistration) {
+        let (handle, registration) = AbortHandle::new_pair();
+        let id = self.next_future.replace(self.next_future.get() + 1);
+
+        self.futures.borrow_mut()
+            .entry(scope)
+            .or_default()
+            .insert(id, handle);
+
+        (id, registration)
+    }
+
+    pub(crate) fn complete_future(&self, scope: &Path, id: u64) {
+        if let Some(futures) = self.futures.borrow_mut().get_mut(scope) {
+            futures.remove(&id);
+        }
+    }
+
+    /// Abort every future that belongs to the component at `scope`.
+    pub(crate) fn cancel_futures(&self, scope: &Path) {
+        let futures = self.futures.borrow_mut().remove(scope);
+
+        for handle in futures.into_iter().flat_map(|futures| futures.into_values()) {
+            handle.abort();
+        }
+    }
+
+    pub(crate) fn schedule_render(&self) {
+        self.render.set(!self.closed.get());
+    }
+
+    /// Schedule a render of the component at `scope` and its descendants, a full render covers it.
+    pub(crate) fn schedule_partial(&self, scope: Path) {
+        if !self.closed.get() {
+            self.dirty.borrow_mut().insert(scope);
+        }
+    }
+
+    /// Returns the render that has been scheduled since the last call, if any.
+    pub(crate) fn take_render(&self) -> Option<Render> {
+        let dirty = self.dirty.take();
+
+        match self.render.replace(false) {
+            true => Some(Render::Full),
+            false => (!dirty.is_empty()).then_some(Render::Partial(dirty)),
+        }
+    }
+
+    /// Try to become the one draining the queue, returns [`None`] if the queue is already
+    /// being drained further up the stack.
+    pub(crate) fn acquire(&self) -> Option<SchedulerGuard<'_>> {
+        if self.closed.get() {
+            return None;
+        }
+
+        (!self.running.replace(true)).then_some(SchedulerGuard { scheduler: self })
+    }
+
+    /// Stop scheduling anything, pending messages and renders are dropped and every future is aborted.
+    pub(crate) fn close(&self) {
+        self.closed.set(true);
+        self.render.set(false);
+        self.dirty.borrow_mut().clear();
+
+        self.queue.borrow_mut().clear();
+        self.mounted.borrow_mut().clear();
+
+        for handle in self.futures.take().into_values().flat_map(|futures| futures.into_values()) {
+            handle.abort();
+        }
+    }
+}
+
+pub(crate) struct SchedulerGuard<'a> {
+    scheduler: &'a Scheduler,
+}
+
+impl Drop for SchedulerGuard<'_> {
+    fn drop(&mut self) {
+        self.scheduler.running.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frontend::render::Renderer;
+    use crate::frontend::html::path::Path;
+    use crate::frontend::html::Html;
+    use crate::frontend::{Component, Link};
+
+    use std::cell::RefCell;
+
+    thread_local! {
+        static HANDLED: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct Relay;
+
+    impl Component for Relay {
+        type Message = &'static str;
+        type Properties = ();
+
+        fn create() -> Relay { Relay }
+
+        fn callback(&mut self, message: &&'static str, link: Link<Relay>) {
+            if *message == "a" {
+                link.send_message("b");
+            }
+
+            // A message handled right away would be recorded before this one.
+            HANDLED.with_borrow_mut(|handled| handled.push(message));
+        }
+
+        fn view(&self, _: ()) -> Html {
+            Html::default()
+        }
+    }
+
+    #[test]
+    fn messages_sent_from_a_callback_are_deferred() {
+        let renderer = Renderer::detached(Relay);
+
+        renderer.render();
+
+        Link::<Relay>::new(renderer, Path::new()).send_message("a");
+
+        assert_eq!(HANDLED.with_borrow(|handled| handled.join(",")), "a,b");
+    }
+}
@@ -0,0 +1,413 @@
+//! Signals are fine-grained reactive state.
+//!
+//! A [`Signal`] can be used directly as a template or an attribute in [`html!`](crate::prelude::html). The renderer remembers
+//! which text nodes and attributes read which signal, writing to the signal will then only patch those locations in the
+//! DOM without running the [`view`](crate::frontend::Component::view) of any component.
+//!
+//! A [`Memo`] derives a value from other signals and an [`Effect`] runs a closure every time the signals it read change.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//! use stunt::frontend::signal::{Signal, Memo};
+//!
+//! pub struct App {
+//!     count: Signal<usize>,
+//!     double: Memo<usize>,
+//! }
+//!
+//! impl Component for App {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> App {
+//!         let count = Signal::new(0);
+//!         let double = Memo::new({
+//!             let count = count.clone();
+//!
+//!             move || count.get() * 2
+//!         });
+//!
+//!         App {
+//!             count,
+//!             double,
+//!         }
+//!     }
+//!
+//!     fn callback(&mut self, _: &(), _: Link<App>) {
+//!         self.count.update(|count| *count += 1);
+//!     }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         html! {
+//!             <div>
+//!                 <button onclick={ () }>
+//!                     { self.count.clone() }
+//!                 </button>
+//!                 <h1>
+//!                     { self.double.clone() }
+//!                 </h1>
+//!             </div>
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+
+thread_local! {
+    static OBSERVER: RefCell<Option<Weak<dyn Observer>>> = const { RefCell::new(None) };
+}
+
+/// Something that reruns when a signal it read changes.
+trait Observer {
+    fn notify(&self);
+}
+
+/// Restores the previous observer when dropped, also when the tracked closure panics.
+struct Tracking {
+    previous: Option<Weak<dyn Observer>>,
+}
+
+impl Drop for Tracking {
+    fn drop(&mut self) {
+        OBSERVER.set(self.previous.take());
+    }
+}
+
+/// Run `f` with `observer` subscribed to every signal that is read.
+fn track<R>(observer: Weak<dyn Observer>, f: impl FnOnce() -> R) -> R {
+    let _tracking = Tracking {
+        previous: OBSERVER.replace(Some(observer)),
+    };
+
+    f()
+}
+
+#[derive(Default)]
+struct Subscribers {
+    observers: RefCell<Vec<Weak<dyn Observer>>>,
+}
+
+impl Subscribers {
+    /// Subscribe the current observer, if any.
+    fn track(&self) {
+        if let Some(observer) = OBSERVER.with_borrow(|observer| observer.clone()) {
+            let mut observers = self.observers.borrow_mut();
+
+            if !observers.iter().any(|subscribed| Weak::ptr_eq(subscribed, &observer)) {
+                observers.push(observer);
+            }
+        }
+    }
+
+    /// Notify every subscribed observer. Observers resubscribe when they rerun, so a signal that
+    /// is no longer read notifies an observer one last time and then drops it.
+    fn notify(&self) {
+        let observers = self.observers.take();
+
+        for observer in observers.iter().filter_map(|observer| observer.upgrade()) {
+            observer.notify();
+        }
+    }
+}
+
+struct SignalInner<T> {
+    value: RefCell<T>,
+    subscribers: Subscribers,
+}
+
+/// A reactive value. The signal can be cloned and every clone will point to the same value.
+pub struct Signal<T> {
+    inner: Rc<SignalInner<T>>,
+}
+
+impl<T> Clone for Signal<T> {
+    fn clone(&self) -> Signal<T> {
+        Signal {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: 'static> Signal<T> {
+    /// Create a new signal.
+    pub fn new(value: T) -> Signal<T> {
+        Signal {
+            inner: Rc::new(SignalInner {
+                value: RefCell::new(value),
+                subscribers: Subscribers::default(),
+            }),
+        }
+    }
+
+    /// Get a clone of the value.
+    pub fn get(&self) -> T
+    where
+        T: Clone
+    {
+        self.with(|value| value.clone())
+    }
+
+    /// Read the value by reference.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.inner.subscribers.track();
+
+        f(&self.inner.value.borrow())
+    }
+
+    /// Replace the value and notify everything that read it.
+    pub fn set(&self, value: T) {
+        self.update(|previous| *previous = value);
+    }
+
+    /// Mutate the value in place and notify everything that read it.
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.inner.value.borrow_mut());
+
+        self.inner.subscribers.notify();
+    }
+}
+
+struct MemoInner<T> {
+    value: RefCell<Option<T>>,
+    compute: Box<dyn Fn() -> T>,
+    subscribers: Subscribers,
+    this: Weak<MemoInner<T>>,
+}
+
+impl<T: PartialEq + 'static> MemoInner<T> {
+    /// Compute the value, returns true if it changed.
+    fn compute(&self) -> bool {
+        let value = track(self.this.clone(), || (self.compute)());
+        let mut previous = self.value.borrow_mut();
+
+        if previous.as_ref() == Some(&value) {
+            return false;
+        }
+
+        previous.replace(value);
+
+        true
+    }
+}
+
+impl<T: PartialEq + 'static> Observer for MemoInner<T> {
+    fn notify(&self) {
+        if self.compute() {
+            self.subscribers.notify();
+        }
+    }
+}
+
+/// A value derived from other signals. The memo is only recomputed when the signals it read change,
+/// and only notifies its own readers when the result is different.
+pub struct Memo<T> {
+    inner: Rc<MemoInner<T>>,
+}
+
+impl<T> Clone for Memo<T> {
+    fn clone(&self) -> Memo<T> {
+        Memo {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: PartialEq + 'static> Memo<T> {
+    /// Create a new memo.
+    pub fn new(compute: impl Fn() -> T + 'static) -> Memo<T> {
+        let inner = Rc::new_cyclic(|this| MemoInner {
+            value: RefCell::new(None),
+            compute: Box::new(compute),
+            subscribers: Subscribers::default(),
+            this: this.clone(),
+        });
+
+        inner.compute();
+
+        Memo {
+            inner,
+        }
+    }
+
+    /// Get a clone of the value.
+    pub fn get(&self) -> T
+    where
+        T: Clone
+    {
+        self.with(|value| value.clone())
+    }
+
+    /// Read the value by reference.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.inner.subscribers.track();
+
+        f(self.inner.value.borrow().as_ref().expect("memo is computed on creation"))
+    }
+}
+
+struct EffectInner {
+    run: Box<dyn Fn()>,
+    this: Weak<EffectInner>,
+}
+
+impl Observer for EffectInner {
+    fn notify(&self) {
+        track(self.this.clone(), || (self.run)());
+    }
+}
+
+/// An effect runs a closure immediately and every time the signals it read change.
+///
+/// The effect stops when it is dropped.
+#[must_use = "the effect stops when it is dropped"]
+pub struct Effect {
+    _inner: Rc<EffectInner>,
+}
+
+impl Effect {
+    /// Create a new effect.
+    pub fn new(run: impl Fn() + 'static) -> Effect {
+        let inner = Rc::new_cyclic(|this: &Weak<EffectInner>| EffectInner {
+            run: Box::new(run),
+            this: this.clone(),
+        });
+
+        inner.notify();
+
+        Effect {
+            _inner: inner,
+        }
+    }
+
+    /// Like [`Effect::new`] but the first run only subscribes, `f` is called with the values from the later runs.
+    pub(crate) fn on_change<T: 'static>(read: impl Fn() -> T + 'static, f: impl Fn(T) + 'static) -> Effect {
+        let first = Cell::new(true);
+
+        Effect::new(move || {
+            let value = read();
+
+            if !first.replace(false) {
+                f(value);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn effects_rerun_when_a_signal_they_read_changes() {
+        let signal = Signal::new(1);
+        let runs = Rc::new(Cell::new(0));
+
+        let _effect = Effect::new({
+            let signal = signal.clone();
+            let runs = runs.clone();
+
+            move || {
+                signal.get();
+                runs.set(runs.get() + 1);
+            }
+        });
+
+        signal.set(2);
+        signal.set(3);
+
+        assert_eq!(runs.get(), 3);
+    }
+
+    #[test]
+    fn dropped_effects_stop_running() {
+        let signal = Signal::new(1);
+        let runs = Rc::new(Cell::new(0));
+
+        let effect = Effect::new({
+            let signal = signal.clone();
+            let runs = runs.clone();
+
+            move || {
+                signal.get();
+                runs.set(runs.get() + 1);
+            }
+        });
+
+        drop(effect);
+
+        signal.set(2);
+
+        assert_eq!(runs.get(), 1);
+    }
+
+    #[test]
+    fn memos_only_notify_when_their_value_changes() {
+        let signal = Signal::new(1);
+        let parity = Memo::new({
+            let signal = signal.clone();
+
+            move || signal.get() % 2
+        });
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        let _effect = Effect::new({
+            let parity = parity.clone();
+            let seen = seen.clone();
+
+            move || seen.borrow_mut().push(parity.get())
+        });
+
+        signal.set(3);
+        signal.set(4);
+
+        assert_eq!(*seen.borrow(), vec![1, 0]);
+    }
+
+    #[test]
+    fn signals_that_are_no_longer_read_are_dropped_after_one_more_change() {
+        let condition = Signal::new(true);
+        let signal = Signal::new(0);
+        let runs = Rc::new(Cell::new(0));
+
+        let _effect = Effect::new({
+            let condition = condition.clone();
+            let signal = signal.clone();
+            let runs = runs.clone();
+
+            move || {
+                if condition.get() {
+                    signal.get();
+                }
+
+                runs.set(runs.get() + 1);
+            }
+        });
+
+        condition.set(false);
+        signal.set(1);
+        signal.set(2);
+
+        assert_eq!(runs.get(), 3);
+    }
+
+    #[test]
+    fn the_observer_is_restored_when_a_tracked_closure_panics() {
+        let signal = Signal::new(0);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| Effect::new(|| panic!("failing effect"))));
+
+        assert!(result.is_err());
+
+        signal.get();
+
+        assert!(OBSERVER.with_borrow(|observer| observer.is_none()));
+        assert!(signal.inner.subscribers.observers.borrow().is_empty());
+    }
+}
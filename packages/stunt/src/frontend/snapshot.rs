@@ -0,0 +1,360 @@
+//! Snapshots are a stable representation of rendered html that doesnt need a DOM.
+//!
+//! A [`Snapshot`] contains the elements with their attributes and the names of the events they listen to, both sorted
+//! by name, the text, and the components that rendered them with their [`Path`](crate::frontend::html::path::Path). It
+//! pretty-prints with [`Display`](std::fmt::Display) and can be serialized with serde, so the output of a component
+//! can be compared between versions.
+//!
+//! Components are recognized by the nodes they rendered, a component that renders no nodes isnt part of the snapshot.
+//!
+//! Snapshots are taken of [`Html`] with [`Html::snapshot`], or of a mounted component with
+//! [`TestRenderer::snapshot`](crate::testing::TestRenderer::snapshot).
+//!
+//! ## Example
+//! ```rust
+//! use stunt::prelude::*;
+//!
+//! #[derive(Properties, Clone)]
+//! pub struct BadgeProperties {
+//!     label: String,
+//! }
+//!
+//! pub struct Badge;
+//!
+//! impl Component for Badge {
+//!     type Message = ();
+//!     type Properties = BadgeProperties;
+//!
+//!     fn create() -> Badge { Badge }
+//!
+//!     fn view(&self, properties: BadgeProperties) -> Html {
+//!         html! {
+//!             <span title={ "badge" } class={ "badge" } onclick={ () }>
+//!                 { properties.label }
+//!             </span>
+//!         }
+//!     }
+//! }
+//!
+//! let html = html! {
+//!     <div>
+//!         <Badge label={ String::from("new") } />
+//!     </div>
+//! };
+//!
+//! assert_eq!(html.snapshot().to_string(), concat!(
+//!     "<div>\n",
+//!     "  <Badge path=\"/element[0]/Badge[0]\">\n",
+//!     "    <span class=\"badge\" title=\"badge\" onclick>\n",
+//!     "      \"new\"\n",
+//!     "    </span>\n",
+//!     "  </Badge>\n",
+//!     "</div>\n",
+//! ));
+//! ```
+
+use crate::frontend::virtual_dom::VirtualNode;
+use crate::frontend::html::path::Path;
+use crate::frontend::render::Renderer;
+use crate::frontend::html::Html;
+use crate::frontend::Component;
+
+use serde::{Serialize, Deserialize};
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+
+/// A node of a [`Snapshot`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotNode {
+    /// The nodes rendered by a component, only present if the component rendered any.
+    Component {
+        /// The name of the component.
+        name: String,
+
+        /// The path of the component.
+        path: String,
+
+        /// The nodes the component rendered.
+        children: Vec<SnapshotNode>,
+    },
+
+    /// An element.
+    Element {
+        /// The name of the element.
+        tag: String,
+
+        /// The attributes of the element, sorted by name.
+        attributes: BTreeMap<String, String>,
+
+        /// The events the element listens to without the `on` prefix, sorted by name.
+        events: Vec<String>,
+
+        /// The children of the element.
+        children: Vec<SnapshotNode>,
+    },
+
+    /// A text node.
+    Text(String),
+}
+
+impl SnapshotNode {
+    /// Group the nodes by the component below `scope` that rendered them.
+    fn group(nodes: &[VirtualNode], scope: &Path) -> Vec<SnapshotNode> {
+        let mut snapshot = Vec::new();
+        let mut nodes = nodes.iter().peekable();
+
+        while let Some(node) = nodes.next() {
+            match node.scope().child_component(scope) {
+                Some(component) => {
+                    let mut group = vec![node.clone()];
+
+                    while let Some(next) = nodes.next_if(|next| next.scope().child_component(scope).as_ref() == Some(&component)) {
+                        group.push(next.clone());
+                    }
+
+                    snapshot.push(SnapshotNode::Component {
+                        name: component.name().unwrap_or_default().to_string(),
+                        path: component.to_string(),
+                        children: SnapshotNode::group(&group, &component),
+                    });
+                },
+                None => snapshot.push(SnapshotNode::node(node)),
+            }
+        }
+
+        snapshot
+    }
+
+    fn node(node: &VirtualNode) -> SnapshotNode {
+        match (node.tag(), node.text()) {
+            (Some(tag), _) => {
+                let mut events = node.callbacks()
+                    .map(|(event, _, _)| event.to_string())
+                    .collect::<Vec<String>>();
+
+                events.sort();
+
+                SnapshotNode::Element {
+                    tag: tag.to_string(),
+                    attributes: node.attributes().into_iter().collect(),
+                    events,
+                    children: SnapshotNode::group(&node.children(), node.scope()),
+                }
+            },
+            (None, text) => SnapshotNode::Text(text.unwrap_or_default()),
+        }
+    }
+
+    fn write(&self, depth: usize, f: &mut fmt::Formatter) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+
+        match self {
+            SnapshotNode::Component { name, path, children } => {
+                writeln!(f, "{}<{} path={:?}>", indent, name, path)?;
+
+                for child in children {
+                    child.write(depth + 1, f)?;
+                }
+
+                writeln!(f, "{}</{}>", indent, name)
+            },
+            SnapshotNode::Element { tag, attributes, events, children } => {
+                let mut open = format!("{}<{}", indent, tag);
+
+                for (name, value) in attributes {
+                    write!(open, " {}={:?}", name, value)?;
+                }
+
+                for event in events {
+                    write!(open, " on{}", event)?;
+                }
+
+                if children.is_empty() {
+                    return writeln!(f, "{} />", open);
+                }
+
+                writeln!(f, "{}>", open)?;
+
+                for child in children {
+                    child.write(depth + 1, f)?;
+                }
+
+                writeln!(f, "{}</{}>", indent, tag)
+            },
+            SnapshotNode::Text(text) => writeln!(f, "{}{:?}", indent, text),
+        }
+    }
+}
+
+/// A stable representation of rendered html, see the [module documentation](self).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Snapshot {
+    nodes: Vec<SnapshotNode>,
+}
+
+impl Snapshot {
+    /// Take a snapshot of the children of a root node.
+    pub(crate) fn new(root: &VirtualNode) -> Snapshot {
+        Snapshot {
+            nodes: SnapshotNode::group(&root.children(), &Path::new()),
+        }
+    }
+
+    /// The top level nodes.
+    pub fn nodes(&self) -> &[SnapshotNode] {
+        &self.nodes
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for node in self.nodes.iter() {
+            node.write(0, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The root of the renderer that renders html for a snapshot.
+struct Detached;
+
+impl Component for Detached {
+    type Message = ();
+    type Properties = ();
+
+    fn create() -> Detached { Detached }
+
+    fn view(&self, _: ()) -> Html {
+        Html::default()
+    }
+}
+
+impl Html {
+    /// Take a [`Snapshot`] of the html, the components in it are rendered without a DOM.
+    pub fn snapshot(&self) -> Snapshot {
+        let renderer = Renderer::detached(Detached);
+
+        Snapshot::new(&renderer.render_html(self.clone(), Path::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stunt_macro::html;
+
+    struct Edited;
+
+    struct Field;
+
+    impl Component for Field {
+        type Message = Edited;
+        type Properties = ();
+
+        fn create() -> Field { Field }
+
+        fn view(&self, _: ()) -> Html {
+            html! {
+                <input value={ "a" } name={ "text" } oninput={ Edited } onchange={ Edited } />
+            }
+        }
+    }
+
+    struct Form;
+
+    impl Component for Form {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Form { Form }
+
+        fn view(&self, _: ()) -> Html {
+            html! {
+                <fieldset>
+                    <Field />
+                    <Field />
+                </fieldset>
+            }
+        }
+    }
+
+    struct Nothing;
+
+    impl Component for Nothing {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Nothing { Nothing }
+
+        fn view(&self, _: ()) -> Html {
+            Html::default()
+        }
+    }
+
+    #[test]
+    fn attributes_and_events_are_sorted() {
+        let snapshot = html! { <Field /> }.snapshot();
+
+        let [SnapshotNode::Component { children, .. }] = snapshot.nodes() else { panic!("expected a component") };
+        let [SnapshotNode::Element { attributes, events, .. }] = children.as_slice() else { panic!("expected an element") };
+
+        assert_eq!(attributes.keys().collect::<Vec<&String>>(), vec!["name", "value"]);
+        assert_eq!(events, &vec![String::from("change"), String::from("input")]);
+    }
+
+    #[test]
+    fn components_are_grouped_with_their_path() {
+        let snapshot = html! {
+            <form>
+                <Form />
+            </form>
+        }.snapshot();
+
+        assert_eq!(snapshot.to_string(), concat!(
+            "<form>\n",
+            "  <Form path=\"/element[0]/Form[0]\">\n",
+            "    <fieldset>\n",
+            "      <Field path=\"/element[0]/Form[0]/element[0]/Field[0]\">\n",
+            "        <input name=\"text\" value=\"a\" onchange oninput />\n",
+            "      </Field>\n",
+            "      <Field path=\"/element[0]/Form[0]/element[0]/Field[1]\">\n",
+            "        <input name=\"text\" value=\"a\" onchange oninput />\n",
+            "      </Field>\n",
+            "    </fieldset>\n",
+            "  </Form>\n",
+            "</form>\n",
+        ));
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_serde() {
+        let snapshot = html! {
+            <form>
+                <Form />
+                { "sent" }
+            </form>
+        }.snapshot();
+
+        let json = serde_json::to_string(&snapshot).expect("snapshots serialize");
+
+        assert!(json.starts_with("[{\"element\":{\"tag\":\"form\""));
+        assert_eq!(serde_json::from_str::<Snapshot>(&json).expect("snapshots deserialize"), snapshot);
+    }
+
+    #[test]
+    fn components_without_nodes_are_left_out() {
+        let snapshot = html! {
+            <p>
+                <Nothing />
+            </p>
+        }.snapshot();
+
+        assert_eq!(snapshot.to_string(), "<p />\n");
+    }
+}
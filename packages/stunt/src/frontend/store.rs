@@ -0,0 +1,424 @@
+//! A [`Store`] holds global state that is shared between components.
+//!
+//! The state is only changed by dispatching actions to the reducer of the store. Components subscribe
+//! to a slice of the state and are re-rendered when their slice changes, together with their descendants.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//! use stunt::frontend::store::Store;
+//!
+//! pub enum Action {
+//!     Increment,
+//! }
+//!
+//! #[derive(Default)]
+//! pub struct State {
+//!     count: usize,
+//! }
+//!
+//! pub struct Counter {
+//!     store: Store<State, Action>,
+//! }
+//!
+//! impl Component for Counter {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> Counter {
+//!         Counter {
+//!             store: Store::new(State::default(), |state: &mut State, action| match action {
+//!                 Action::Increment => state.count += 1,
+//!             }),
+//!         }
+//!     }
+//!
+//!     fn once(&mut self, link: Link<Counter>) {
+//!         self.store.subscribe(&link, |state| state.count);
+//!     }
+//!
+//!     fn callback(&mut self, _: &(), _: Link<Counter>) {
+//!         self.store.dispatch(Action::Increment);
+//!     }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         html! {
+//!             <button onclick={ () }>
+//!                 { self.store.select(|state| state.count) }
+//!             </button>
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::frontend::render::Renderer;
+use crate::frontend::html::path::Path;
+use crate::frontend::{BaseComponent, Component, Link};
+
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::VecDeque;
+use std::rc::{Rc, Weak};
+
+
+type Reducer<S, A> = Box<dyn Fn(&mut S, A)>;
+
+struct Subscriber<S> {
+    component: Weak<RefCell<dyn BaseComponent>>,
+    renderer: Renderer,
+    scope: Path,
+    changed: Box<dyn FnMut(&S) -> bool>,
+}
+
+struct Inner<S, A> {
+    state: RefCell<S>,
+    reducer: Reducer<S, A>,
+    subscribers: RefCell<Vec<Subscriber<S>>>,
+    pending: RefCell<VecDeque<A>>,
+    dispatching: Cell<bool>,
+}
+
+/// Clears the dispatching flag when dropped, also when the reducer panics.
+struct Dispatching<'a> {
+    flag: &'a Cell<bool>,
+}
+
+impl Drop for Dispatching<'_> {
+    fn drop(&mut self) {
+        self.flag.set(false);
+    }
+}
+
+/// A store of state with a reducer. The store can be cloned and every clone will point to the same state.
+pub struct Store<S, A> {
+    inner: Rc<Inner<S, A>>,
+}
+
+impl<S, A> Clone for Store<S, A> {
+    fn clone(&self) -> Store<S, A> {
+        Store {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S: 'static, A: 'static> Store<S, A> {
+    /// Create a new store with an initial state and a reducer.
+    pub fn new(state: S, reducer: impl Fn(&mut S, A) + 'static) -> Store<S, A> {
+        Store {
+            inner: Rc::new(Inner {
+                state: RefCell::new(state),
+                reducer: Box::new(reducer),
+                subscribers: RefCell::new(Vec::new()),
+                pending: RefCell::new(VecDeque::new()),
+                dispatching: Cell::new(false),
+            }),
+        }
+    }
+
+    /// Borrow the current state.
+    pub fn state(&self) -> Ref<'_, S> {
+        self.inner.state.borrow()
+    }
+
+    /// Select a slice of the current state.
+    pub fn select<T>(&self, selector: impl Fn(&S) -> T) -> T {
+        selector(&self.inner.state.borrow())
+    }
+
+    /// Dispatch an action to the reducer.
+    ///
+    /// The components whose selected slice changed are re-rendered. Actions dispatched
+    /// while the reducer is running are queued and reduced in order afterwards.
+    pub fn dispatch(&self, action: A) {
+        self.inner.pending.borrow_mut().push_back(action);
+
+        if self.inner.dispatching.replace(true) {
+            return;
+        }
+
+        let dispatching = Dispatching { flag: &self.inner.dispatching };
+
+        loop {
+            let Some(action) = self.inner.pending.borrow_mut().pop_front() else { break };
+
+            (self.inner.reducer)(&mut self.inner.state.borrow_mut(), action);
+        }
+
+        drop(dispatching);
+
+        self.notify();
+    }
+
+    /// Subscribe a component to a slice of the state. The component is re-rendered every time
+    /// the slice changes, until it unmounts.
+    ///
+    /// A component has a single subscription, subscribing again replaces its selector.
+    pub fn subscribe<C: Component, T: PartialEq + 'static>(&self, link: &Link<C>, selector: impl Fn(&S) -> T + 'static) {
+        let Some(component) = link.renderer.get(&link.scope) else { return };
+        let component = Rc::downgrade(&component);
+
+        let mut selected = self.select(&selector);
+
+        let mut subscribers = self.inner.subscribers.borrow_mut();
+
+        subscribers.retain(|subscriber| subscriber.scope != link.scope || !Weak::ptr_eq(&subscriber.component, &component));
+
+        subscribers.push(Subscriber {
+            component,
+            renderer: link.renderer.clone(),
+            scope: link.scope.clone(),
+            changed: Box::new(move |state| {
+                let slice = selector(state);
+
+                if slice == selected {
+                    return false;
+                }
+
+                selected = slice;
+
+                true
+            }),
+        });
+    }
+
+    fn notify(&self) {
+        let mut changed: Vec<(Renderer, Vec<Path>)> = Vec::new();
+
+        {
+            let state = self.inner.state.borrow();
+
+            self.inner.subscribers.borrow_mut().retain_mut(|subscriber| {
+                let mounted = subscriber.component.strong_count() > 0;
+
+                if mounted && (subscriber.changed)(&state) {
+                    match changed.iter_mut().find(|(renderer, _)| renderer.is(&subscriber.renderer)) {
+                        Some((_, scopes)) => scopes.push(subscriber.scope.clone()),
+                        None => changed.push((subscriber.renderer.clone(), vec![subscriber.scope.clone()])),
+                    }
+                }
+
+                mounted
+            });
+        }
+
+        for (renderer, scopes) in changed {
+            renderer.render_scopes(scopes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::html::Html;
+
+    use stunt_macro::html;
+
+    thread_local! {
+        static VIEWS: Cell<usize> = const { Cell::new(0) };
+        static SHARED: Store<State, Action> = store();
+        static PAGE_VIEWS: Cell<usize> = const { Cell::new(0) };
+        static BADGE_VIEWS: Cell<usize> = const { Cell::new(0) };
+        static LABEL_VIEWS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Counter;
+
+    impl Component for Counter {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Counter { Counter }
+
+        fn view(&self, _: ()) -> Html {
+            VIEWS.set(VIEWS.get() + 1);
+
+            Html::default()
+        }
+    }
+
+    struct Page;
+
+    impl Component for Page {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Page { Page }
+
+        fn view(&self, _: ()) -> Html {
+            PAGE_VIEWS.set(PAGE_VIEWS.get() + 1);
+
+            html! {
+                <main>
+                    <Badge />
+                </main>
+            }
+        }
+    }
+
+    struct Badge;
+
+    impl Component for Badge {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Badge { Badge }
+
+        fn once(&mut self, link: Link<Badge>) {
+            SHARED.with(|store| store.subscribe(&link, |state| state.count));
+        }
+
+        fn view(&self, _: ()) -> Html {
+            BADGE_VIEWS.set(BADGE_VIEWS.get() + 1);
+
+            let count = SHARED.with(|store| store.select(|state| state.count));
+
+            html! {
+                <span>
+                    { count }
+                    <Label />
+                </span>
+            }
+        }
+    }
+
+    struct Label;
+
+    impl Component for Label {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Label { Label }
+
+        fn view(&self, _: ()) -> Html {
+            LABEL_VIEWS.set(LABEL_VIEWS.get() + 1);
+
+            html! {
+                <b>
+                    { "clicks" }
+                </b>
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct State {
+        count: usize,
+        other: usize,
+    }
+
+    enum Action {
+        Count,
+        Other,
+    }
+
+    fn store() -> Store<State, Action> {
+        Store::new(State::default(), |state: &mut State, action| match action {
+            Action::Count => state.count += 1,
+            Action::Other => state.other += 1,
+        })
+    }
+
+    fn mount() -> (Renderer, Link<Counter>) {
+        let renderer = Renderer::detached(Counter);
+
+        renderer.render();
+
+        (renderer.clone(), Link::new(renderer, Path::new()))
+    }
+
+    #[test]
+    fn dispatch_reduces_the_state() {
+        let store = store();
+
+        store.dispatch(Action::Count);
+        store.dispatch(Action::Count);
+        store.dispatch(Action::Other);
+
+        assert_eq!(store.select(|state| (state.count, state.other)), (2, 1));
+    }
+
+    #[test]
+    fn subscribers_are_rendered_when_their_slice_changes() {
+        let store = store();
+        let (_renderer, link) = mount();
+
+        store.subscribe(&link, |state| state.count);
+
+        let views = VIEWS.get();
+
+        store.dispatch(Action::Other);
+
+        assert_eq!(VIEWS.get(), views);
+
+        store.dispatch(Action::Count);
+
+        assert_eq!(VIEWS.get(), views + 1);
+    }
+
+    #[test]
+    fn subscribing_again_replaces_the_subscription() {
+        let store = store();
+        let (_renderer, link) = mount();
+
+        store.subscribe(&link, |state| state.count);
+        store.subscribe(&link, |state| state.other);
+
+        assert_eq!(store.inner.subscribers.borrow().len(), 1);
+
+        let views = VIEWS.get();
+
+        store.dispatch(Action::Count);
+
+        assert_eq!(VIEWS.get(), views);
+    }
+
+    #[test]
+    fn unmounted_subscribers_are_dropped() {
+        let store = store();
+        let (renderer, link) = mount();
+
+        store.subscribe(&link, |state| state.count);
+
+        renderer.unmount();
+
+        store.dispatch(Action::Count);
+
+        assert!(store.inner.subscribers.borrow().is_empty());
+    }
+
+    #[test]
+    fn only_subscribers_and_their_descendants_are_rendered() {
+        let renderer = Renderer::detached(Page);
+
+        renderer.render();
+
+        assert_eq!((PAGE_VIEWS.get(), BADGE_VIEWS.get(), LABEL_VIEWS.get()), (1, 1, 1));
+
+        SHARED.with(|store| store.dispatch(Action::Count));
+
+        assert_eq!((PAGE_VIEWS.get(), BADGE_VIEWS.get(), LABEL_VIEWS.get()), (1, 2, 2));
+        assert_eq!(renderer.tree().inner_html(), "<main><span>1<b>clicks</b></span></main>");
+
+        renderer.render();
+
+        assert_eq!((PAGE_VIEWS.get(), BADGE_VIEWS.get(), LABEL_VIEWS.get()), (2, 3, 3));
+    }
+
+    #[test]
+    fn a_panicking_reducer_doesnt_block_the_store() {
+        let store = Store::new(0, |state: &mut usize, fail: bool| {
+            assert!(!fail, "reducer failed");
+
+            *state += 1;
+        });
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| store.dispatch(true)));
+
+        assert!(result.is_err());
+
+        store.dispatch(false);
+
+        assert_eq!(*store.state(), 1);
+    }
+}
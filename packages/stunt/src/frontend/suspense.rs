@@ -0,0 +1,193 @@
+//! Suspense renders a fallback until the content of a future is ready.
+//!
+//! In the browser the future is spawned once the [`Suspense`] is mounted and its content replaces the fallback
+//! when it resolves. When streamed from the server with [`ssr::render_to_stream`](crate::ssr::render_to_stream),
+//! the fallback is part of the first chunk and the content is sent in a later chunk as soon as it resolves.
+//!
+//! The content is loaded once, a new future passed to a mounted suspense is ignored. When a streamed page is
+//! hydrated, the suspense keeps the fallback or content sent by the server until its own content resolved.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//! use stunt::frontend::suspense::{Deferred, Suspense};
+//!
+//! async fn load_posts() -> Vec<String> {
+//!     vec![String::from("first post")]
+//! }
+//!
+//! pub struct App;
+//!
+//! impl Component for App {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> App { App }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         let posts = Deferred::new(async {
+//!             let posts = load_posts().await;
+//!
+//!             html! {
+//!                 <ul>
+//!                     {
+//!                         posts.into_iter()
+//!                             .map(|post| html! { <li> { post } </li> })
+//!                             .collect::<Vec<Html>>()
+//!                     }
+//!                 </ul>
+//!             }
+//!         });
+//!
+//!         html! {
+//!             <Suspense content={ posts }>
+//!                 <p>
+//!                     { "loading posts" }
+//!                 </p>
+//!             </Suspense>
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::frontend::html::{Children, Html, Template, AttributeValue};
+use crate::frontend::html::path::Path;
+use crate::frontend::render::Renderer;
+use crate::frontend::{Component, Link};
+
+use stunt_macro::Properties;
+
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+
+/// The attribute of the placeholder while the fallback is rendered, set to the id of the content when it is
+/// streamed. A hydrated placeholder with an id keeps the DOM sent by the server until the content resolved.
+pub(crate) const PENDING: &str = "data-stunt-suspense";
+
+/// The future of a [`Deferred`].
+pub(crate) type DeferredFuture = Pin<Box<dyn Future<Output = Html>>>;
+
+/// A future that renders the content of a [`Suspense`].
+#[derive(Clone)]
+pub struct Deferred {
+    future: Rc<RefCell<Option<DeferredFuture>>>,
+}
+
+impl Deferred {
+    /// Create a new deferred content.
+    pub fn new(future: impl Future<Output = Html> + 'static) -> Deferred {
+        Deferred {
+            future: Rc::new(RefCell::new(Some(Box::pin(future)))),
+        }
+    }
+
+    fn take(&self) -> Option<DeferredFuture> {
+        self.future.take()
+    }
+}
+
+/// The deferred content of a renderer that streams its html.
+#[derive(Default)]
+pub(crate) struct Deferreds {
+    streaming: Cell<bool>,
+    next: Cell<usize>,
+    pending: RefCell<Vec<(usize, Path, DeferredFuture)>>,
+}
+
+impl Deferreds {
+    /// Collect the content of every suspense instead of leaving it to the browser.
+    pub(crate) fn stream(&self) {
+        self.streaming.set(true);
+    }
+
+    /// Take the future of the suspense at `scope` if the html is streamed, returns the id of its placeholder.
+    pub(crate) fn defer(&self, scope: Path, content: &Deferred) -> Option<usize> {
+        if !self.streaming.get() {
+            return None;
+        }
+
+        let future = content.take()?;
+        let id = self.next.replace(self.next.get() + 1);
+
+        self.pending.borrow_mut().push((id, scope, future));
+
+        Some(id)
+    }
+
+    /// Take the futures deferred since the last call.
+    pub(crate) fn take(&self) -> Vec<(usize, Path, DeferredFuture)> {
+        self.pending.take()
+    }
+}
+
+/// The message of a [`Suspense`], sent when its content resolved.
+pub struct Resolved(Html);
+
+/// The properties of a [`Suspense`].
+#[derive(Properties, Clone)]
+pub struct SuspenseProperties {
+    /// The content rendered once it resolves.
+    pub content: Deferred,
+
+    /// The fallback rendered until the content resolves.
+    pub children: Children,
+}
+
+/// A component that renders its children until its content resolved.
+pub struct Suspense {
+    content: Option<Html>,
+    pending: RefCell<Option<Deferred>>,
+}
+
+impl Suspense {
+    /// The placeholder wrapping the fallback or content, it doesnt take part in the layout. The fallback is marked
+    /// as [`PENDING`] with the id of the streamed content, if any.
+    fn placeholder(content: Rc<dyn Template>, pending: Option<Option<usize>>) -> Html {
+        let mut attributes = vec![(String::from("style"), Rc::new("display: contents") as Rc<dyn AttributeValue>)];
+
+        if let Some(id) = pending {
+            attributes.push((String::from(PENDING), Rc::new(id.map(|id| id.to_string()).unwrap_or_default())));
+        }
+
+        Html::wrap("stunt-suspense", attributes, content)
+    }
+}
+
+impl Component for Suspense {
+    type Message = Resolved;
+    type Properties = SuspenseProperties;
+
+    fn create() -> Suspense {
+        Suspense {
+            content: None,
+            pending: RefCell::new(None),
+        }
+    }
+
+    fn once(&mut self, link: Link<Suspense>) {
+        if let Some(future) = self.pending.take().and_then(|content| content.take()) {
+            link.send_future(async move { Resolved(future.await) });
+        }
+    }
+
+    fn callback(&mut self, resolved: &Resolved, _: Link<Suspense>) {
+        self.content = Some(resolved.0.clone());
+    }
+
+    fn view(&self, properties: SuspenseProperties) -> Html {
+        if let Some(content) = &self.content {
+            return Suspense::placeholder(Rc::new(content.clone()), None);
+        }
+
+        let id = Renderer::current().and_then(|(renderer, scope)| renderer.defer(scope, &properties.content));
+
+        if id.is_none() {
+            self.pending.borrow_mut().get_or_insert(properties.content);
+        }
+
+        Suspense::placeholder(Rc::new(properties.children), Some(id))
+    }
+}
@@ -0,0 +1,302 @@
+use crate::frontend::virtual_dom::{VirtualKind, VirtualNode};
+use crate::frontend::render::{Renderer, RenderError};
+use crate::frontend::suspense::PENDING;
+
+use wasm_bindgen::prelude::*;
+
+
+/// Elements the page or other libraries put next to the application, they are kept when left over after hydrating.
+const FOREIGN: [&str; 5] = ["script", "template", "noscript", "style", "link"];
+
+/// The parts of a DOM node that decide which nodes are adopted, skipped or removed.
+trait Sibling: Sized {
+    fn node_type(&self) -> u16;
+
+    fn node_name(&self) -> String;
+
+    fn node_value(&self) -> Option<String>;
+
+    fn next_sibling(&self) -> Option<Self>;
+}
+
+impl Sibling for web_sys::Node {
+    fn node_type(&self) -> u16 {
+        web_sys::Node::node_type(self)
+    }
+
+    fn node_name(&self) -> String {
+        web_sys::Node::node_name(self)
+    }
+
+    fn node_value(&self) -> Option<String> {
+        web_sys::Node::node_value(self)
+    }
+
+    fn next_sibling(&self) -> Option<web_sys::Node> {
+        web_sys::Node::next_sibling(self)
+    }
+}
+
+/// Adopt the existing DOM children of `parent` as the nodes of `nodes`.
+///
+/// Matching DOM nodes are kept and only get their listeners attached, mismatching nodes are replaced so the DOM
+/// ends up the same as if it was rendered from scratch. Mismatches are reported in debug builds.
+///
+/// The children of a pending suspense whose content is streamed arent hydrated, the server may have swapped its
+/// content in already or still does so. They are replaced as a whole once the suspense resolves on the client.
+///
+/// Nodes left over after the last child are removed, except the [`FOREIGN`] elements the page may have added.
+pub(super) fn hydrate_children(renderer: Renderer, parent: &web_sys::Node, nodes: &[VirtualNode], document: &web_sys::Document) -> Result<(), RenderError> {
+    let mut cursor = parent.first_child();
+
+    for node in nodes {
+        cursor = skip_comments(cursor);
+
+        match &node.kind {
+            // Empty text isnt part of the server html, so it never has a node to adopt.
+            VirtualKind::Template(text) if text.is_empty() => {
+                parent.insert_before(&node.create(renderer.clone(), document)?, cursor.as_ref())?;
+            },
+            VirtualKind::Template(text) => match cursor.take() {
+                Some(dom) if dom.node_type() == web_sys::Node::TEXT_NODE => {
+                    if dom.node_value().as_deref() != Some(text.as_str()) {
+                        mismatch(|| format!("expected text {:?}, found {:?}", text, dom.node_value().unwrap_or_default()));
+
+                        dom.set_node_value(Some(text));
+                    }
+
+                    cursor = dom.next_sibling();
+
+                    node.handle.replace(Some(dom));
+                },
+                dom => cursor = replace(renderer.clone(), parent, node, dom, document)?,
+            },
+            VirtualKind::Element(element) => {
+                let (whitespace, next) = skip_whitespace(cursor);
+
+                // The page may have whitespace around elements, the application never rendered it.
+                for dom in whitespace {
+                    parent.remove_child(&dom)?;
+                }
+
+                cursor = next;
+
+                match cursor.take() {
+                    Some(dom) if dom.dyn_ref::<web_sys::Element>().is_some_and(|dom| dom.tag_name().eq_ignore_ascii_case(&element.name)) => {
+                        let dom_element = dom.unchecked_ref::<web_sys::Element>();
+
+                        if element.attribute(PENDING).is_some() && dom_element.get_attribute(PENDING).is_some_and(|id| !id.is_empty()) {
+                            node.skipped.set(true);
+
+                            cursor = dom.next_sibling();
+
+                            node.handle.replace(Some(dom));
+
+                            continue;
+                        }
+
+                        for (name, value) in element.attributes.iter() {
+                            if dom_element.get_attribute(name).as_ref() != Some(value) {
+                                mismatch(|| format!("expected attribute {}={:?} on <{}>", name, value, element.name));
+
+                                dom_element.set_attribute(name, value)?;
+                            }
+                        }
+
+                        node.listen(renderer.clone(), dom_element)?;
+
+                        hydrate_children(renderer.clone(), &dom, &element.children, document)?;
+
+                        cursor = dom.next_sibling();
+
+                        node.handle.replace(Some(dom));
+                    },
+                    dom => cursor = replace(renderer.clone(), parent, node, dom, document)?,
+                }
+            },
+        }
+    }
+
+    for dom in leftovers(cursor) {
+        if !is_whitespace(&dom) && dom.node_type() != web_sys::Node::COMMENT_NODE {
+            mismatch(|| format!("unexpected node {}", dom.node_name()));
+        }
+
+        parent.remove_child(&dom)?;
+    }
+
+    Ok(())
+}
+
+/// Create the DOM of `node` in place of the mismatching `dom`, returns the node after it.
+fn replace(renderer: Renderer, parent: &web_sys::Node, node: &VirtualNode, dom: Option<web_sys::Node>, document: &web_sys::Document) -> Result<Option<web_sys::Node>, RenderError> {
+    let created = node.create(renderer, document)?;
+
+    match dom {
+        Some(dom) => {
+            mismatch(|| format!("expected {}, found {}", node.kind.describe(), dom.node_name()));
+
+            let next = dom.next_sibling();
+
+            parent.replace_child(&created, &dom)?;
+
+            Ok(next)
+        },
+        None => {
+            mismatch(|| format!("expected {}, found nothing", node.kind.describe()));
+
+            parent.append_child(&created)?;
+
+            Ok(None)
+        },
+    }
+}
+
+/// Skip the comments that separate adjacent text in the server html.
+fn skip_comments<N: Sibling>(mut cursor: Option<N>) -> Option<N> {
+    while let Some(dom) = cursor.as_ref().filter(|dom| dom.node_type() == web_sys::Node::COMMENT_NODE) {
+        cursor = dom.next_sibling();
+    }
+
+    cursor
+}
+
+/// Skip whitespace and the comments after it, returns the whitespace and the node after it.
+fn skip_whitespace<N: Sibling>(mut cursor: Option<N>) -> (Vec<N>, Option<N>) {
+    let mut whitespace = Vec::new();
+
+    while let Some(dom) = cursor.take() {
+        if !is_whitespace(&dom) {
+            return (whitespace, Some(dom));
+        }
+
+        cursor = skip_comments(dom.next_sibling());
+
+        whitespace.push(dom);
+    }
+
+    (whitespace, None)
+}
+
+/// The nodes from `cursor` on that were rendered by the server and arent part of the application anymore.
+fn leftovers<N: Sibling>(mut cursor: Option<N>) -> Vec<N> {
+    let mut leftovers = Vec::new();
+
+    while let Some(dom) = cursor {
+        cursor = dom.next_sibling();
+
+        if !is_foreign(&dom) {
+            leftovers.push(dom);
+        }
+    }
+
+    leftovers
+}
+
+fn is_whitespace<N: Sibling>(dom: &N) -> bool {
+    dom.node_type() == web_sys::Node::TEXT_NODE && dom.node_value().is_some_and(|text| text.trim().is_empty())
+}
+
+fn is_foreign<N: Sibling>(dom: &N) -> bool {
+    dom.node_type() == web_sys::Node::ELEMENT_NODE && FOREIGN.iter().any(|name| dom.node_name().eq_ignore_ascii_case(name))
+}
+
+#[cfg(debug_assertions)]
+fn mismatch(message: impl FnOnce() -> String) {
+    web_sys::console::warn_1(&format!("hydration mismatch: {}", message()).into());
+}
+
+#[cfg(not(debug_assertions))]
+fn mismatch(_: impl FnOnce() -> String) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::rc::Rc;
+
+    /// A sibling in a list of `(node type, name or value)`.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Node {
+        siblings: Rc<Vec<(u16, &'static str)>>,
+        index: usize,
+    }
+
+    impl Sibling for Node {
+        fn node_type(&self) -> u16 {
+            self.siblings[self.index].0
+        }
+
+        fn node_name(&self) -> String {
+            match self.node_type() {
+                web_sys::Node::ELEMENT_NODE => self.siblings[self.index].1.to_uppercase(),
+                web_sys::Node::TEXT_NODE => String::from("#text"),
+                _ => String::from("#comment"),
+            }
+        }
+
+        fn node_value(&self) -> Option<String> {
+            (self.node_type() != web_sys::Node::ELEMENT_NODE).then(|| self.siblings[self.index].1.to_string())
+        }
+
+        fn next_sibling(&self) -> Option<Node> {
+            (self.index + 1 < self.siblings.len()).then(|| Node { siblings: self.siblings.clone(), index: self.index + 1 })
+        }
+    }
+
+    const ELEMENT: u16 = web_sys::Node::ELEMENT_NODE;
+    const TEXT: u16 = web_sys::Node::TEXT_NODE;
+    const COMMENT: u16 = web_sys::Node::COMMENT_NODE;
+
+    fn first(siblings: Vec<(u16, &'static str)>) -> Option<Node> {
+        Some(Node { siblings: Rc::new(siblings), index: 0 })
+    }
+
+    fn indices(nodes: &[Node]) -> Vec<usize> {
+        nodes.iter().map(|node| node.index).collect()
+    }
+
+    #[test]
+    fn adjacent_text_is_split_by_comments() {
+        // The server renders the text `a` and `b` as `a<!---->b`.
+        let a = first(vec![(TEXT, "a"), (COMMENT, ""), (TEXT, "b")]).unwrap();
+
+        let b = skip_comments(a.next_sibling()).unwrap();
+
+        assert_eq!(b.node_value().as_deref(), Some("b"));
+        assert_eq!(skip_comments(Some(a.clone())), Some(a));
+    }
+
+    #[test]
+    fn whitespace_before_an_element_is_skipped() {
+        let (whitespace, next) = skip_whitespace(first(vec![(TEXT, "\n  "), (COMMENT, ""), (TEXT, " "), (ELEMENT, "div"), (TEXT, " ")]));
+
+        assert_eq!(indices(&whitespace), vec![0, 2]);
+        assert_eq!(next.map(|node| node.index), Some(3));
+
+        let (whitespace, next) = skip_whitespace(first(vec![(TEXT, "text"), (TEXT, " ")]));
+
+        assert!(whitespace.is_empty());
+        assert_eq!(next.map(|node| node.index), Some(0));
+
+        let (whitespace, next) = skip_whitespace(first(vec![(TEXT, " ")]));
+
+        assert_eq!(indices(&whitespace), vec![0]);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn foreign_leftovers_are_kept() {
+        let leftovers = leftovers(first(vec![
+            (ELEMENT, "div"),
+            (ELEMENT, "script"),
+            (TEXT, " "),
+            (ELEMENT, "template"),
+            (COMMENT, ""),
+            (ELEMENT, "style"),
+            (TEXT, "stale"),
+        ]));
+
+        assert_eq!(indices(&leftovers), vec![0, 2, 4, 6]);
+    }
+}
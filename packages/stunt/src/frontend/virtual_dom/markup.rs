@@ -0,0 +1,163 @@
+use crate::frontend::virtual_dom::{VirtualKind, VirtualNode};
+
+
+/// Elements that can not have children and are written without a closing tag.
+const VOID_ELEMENTS: [&str; 13] = ["area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr"];
+
+/// Elements whose text is read by the browser as is, escaping it would change its meaning.
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+
+/// Write the html of a list of sibling nodes.
+pub(super) fn write_nodes(nodes: &[VirtualNode], raw: bool, html: &mut String) {
+    for (index, node) in nodes.iter().enumerate() {
+        // The browser parses adjacent text into a single text node, a comment keeps them apart.
+        if index > 0 && matches!((&nodes[index - 1].kind, &node.kind), (VirtualKind::Template(_), VirtualKind::Template(_))) {
+            html.push_str("<!---->");
+        }
+
+        write_node(node, raw, html);
+    }
+}
+
+fn write_node(node: &VirtualNode, raw: bool, html: &mut String) {
+    match &node.kind {
+        VirtualKind::Template(_) => {
+            let text = node.text().unwrap_or_default();
+
+            match raw {
+                true => escape_raw(&text, html),
+                false => escape(&text, false, html),
+            }
+        },
+        VirtualKind::Element(element) => {
+            html.push('<');
+            html.push_str(&element.name);
+
+            for (name, value) in node.attributes().iter() {
+                html.push(' ');
+                html.push_str(name);
+                html.push_str("=\"");
+
+                escape(value, true, html);
+
+                html.push('"');
+            }
+
+            html.push('>');
+
+            if VOID_ELEMENTS.contains(&element.name.as_str()) {
+                return;
+            }
+
+            write_nodes(&element.children, RAW_TEXT_ELEMENTS.contains(&element.name.as_str()), html);
+
+            html.push_str("</");
+            html.push_str(&element.name);
+            html.push('>');
+        },
+    }
+}
+
+/// Write the text of a raw text element. Nothing else is escaped, but the text can never close its element or open
+/// a comment, a `<` that would do so is followed by a backslash.
+fn escape_raw(text: &str, html: &mut String) {
+    for (index, c) in text.char_indices() {
+        html.push(c);
+
+        let rest = &text[index + c.len_utf8()..];
+
+        let closes = rest.strip_prefix('/').is_some_and(|rest| {
+            RAW_TEXT_ELEMENTS.iter().any(|name| rest.get(..name.len()).is_some_and(|tag| tag.eq_ignore_ascii_case(name)))
+        });
+
+        if c == '<' && (closes || rest.starts_with("!--")) {
+            html.push('\\');
+        }
+    }
+}
+
+fn escape(text: &str, attribute: bool, html: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' if attribute => html.push_str("&quot;"),
+            c => html.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::virtual_dom::VirtualElement;
+    use crate::frontend::html::path::Path;
+
+    use std::rc::Rc;
+
+    fn element(name: &str, attributes: &[(&str, &str)], children: Vec<VirtualNode>) -> VirtualNode {
+        let attributes = attributes.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+
+        VirtualNode::new(Rc::new(Vec::new()), VirtualKind::Element(VirtualElement::new(name.to_string(), attributes, Rc::new(children))), Path::new())
+    }
+
+    fn text(text: &str) -> VirtualNode {
+        VirtualNode::new(Rc::new(Vec::new()), VirtualKind::Template(text.to_string()), Path::new())
+    }
+
+    fn markup(nodes: &[VirtualNode]) -> String {
+        let mut html = String::new();
+
+        write_nodes(nodes, false, &mut html);
+
+        html
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(markup(&[text("<b>\"fish\" & chips</b>")]), "&lt;b&gt;\"fish\" &amp; chips&lt;/b&gt;");
+    }
+
+    #[test]
+    fn attributes_are_escaped() {
+        let html = markup(&[element("a", &[("title", "\"<&>\"")], Vec::new())]);
+
+        assert_eq!(html, "<a title=\"&quot;&lt;&amp;&gt;&quot;\"></a>");
+    }
+
+    #[test]
+    fn void_elements_have_no_closing_tag() {
+        let html = markup(&[element("p", &[], vec![element("br", &[], Vec::new()), element("input", &[("value", "1")], Vec::new())])]);
+
+        assert_eq!(html, "<p><br><input value=\"1\"></p>");
+    }
+
+    #[test]
+    fn raw_text_elements_are_not_escaped() {
+        let html = markup(&[
+            element("script", &[], vec![text("if (a < b && c > d) {}")]),
+            element("style", &[], vec![text("a > b { content: \"&\" }")]),
+            element("div", &[], vec![text("a < b")]),
+        ]);
+
+        assert_eq!(html, "<script>if (a < b && c > d) {}</script><style>a > b { content: \"&\" }</style><div>a &lt; b</div>");
+    }
+
+    #[test]
+    fn raw_text_cant_close_its_element() {
+        let html = markup(&[
+            element("script", &[], vec![text("</script><img src=x onerror=alert(1)>")]),
+            element("style", &[], vec![text("</STYLE><!-- </Script")]),
+        ]);
+
+        assert_eq!(html, "<script><\\/script><img src=x onerror=alert(1)></script><style><\\/STYLE><\\!-- <\\/Script</style>");
+    }
+
+    #[test]
+    fn adjacent_text_is_separated() {
+        let html = markup(&[text("a"), text("b"), element("span", &[], vec![text("c"), text("d")]), text("e")]);
+
+        assert_eq!(html, "a<!---->b<span>c<!---->d</span>e");
+    }
+}
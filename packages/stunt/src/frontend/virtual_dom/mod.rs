@@ -1,31 +1,68 @@
+mod hydrate;
+mod markup;
+mod patch;
+
 use crate::frontend::html::path::{Path, PathNode};
-use crate::frontend::render::Renderer;
-use crate::frontend::Link;
+use crate::frontend::render::{Renderer, RenderError};
+use crate::frontend::signal::Effect;
 
-use std::sync::Arc;
+use std::cell::{Cell, RefCell};
 use std::any::Any;
+use std::rc::Rc;
 
 use wasm_bindgen::prelude::*;
 
 
-#[derive(Debug, Clone, PartialEq)]
+/// The DOM node of a [`VirtualNode`], set once the node is mounted and handed on to the node that replaces it.
+pub(crate) type Handle = Rc<RefCell<Option<web_sys::Node>>>;
+
+/// Matches a node among its siblings when they are diffed, instead of its position.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) enum Key {
+    /// The key given to the node in the html.
+    Node(String),
+
+    /// A node rendered by a keyed component, the nodes of the component move together with it.
+    Component(PathNode, usize),
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Key::Node(key) => f.write_str(key),
+            Key::Component(component, index) => write!(f, "{}/{}", component, index),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum VirtualKind {
     Template(String),
     Element(VirtualElement),
 }
 
 impl VirtualKind {
-    pub fn render(&self) -> String {
+    pub fn children(&self) -> Rc<Vec<VirtualNode>> {
         match self {
-            VirtualKind::Template(_) => String::from("<span></span>"),
-            VirtualKind::Element(element) => element.render(),
+            VirtualKind::Template(_) => Rc::new(Vec::new()),
+            VirtualKind::Element(element) => element.children.clone(),
         }
     }
 
-    pub fn children(&self) -> Arc<Vec<VirtualNode>> {
+    /// Returns true if a node of this kind can be patched into `other` instead of being recreated.
+    fn compatible(&self, other: &VirtualKind) -> bool {
+        match (self, other) {
+            (VirtualKind::Template(_), VirtualKind::Template(_)) => true,
+            (VirtualKind::Element(a), VirtualKind::Element(b)) => a.name == b.name,
+            _ => false,
+        }
+    }
+
+    /// A short description of the node for diagnostics.
+    fn describe(&self) -> String {
         match self {
-            VirtualKind::Template(_) => Arc::new(Vec::new()),
-            VirtualKind::Element(element) => element.children.clone(),
+            VirtualKind::Template(_) => String::from("text"),
+            VirtualKind::Element(element) => format!("<{}>", element.name),
         }
     }
 }
@@ -33,18 +70,12 @@ impl VirtualKind {
 #[derive(Debug, Clone)]
 pub struct VirtualElement {
     name: String,
-    attributes: String,
-    children: Arc<Vec<VirtualNode>>,
-}
-
-impl PartialEq for VirtualElement {
-    fn eq(&self, other: &VirtualElement) -> bool {
-        self.name == other.name && self.attributes == other.attributes
-    }
+    attributes: Vec<(String, String)>,
+    children: Rc<Vec<VirtualNode>>,
 }
 
 impl VirtualElement {
-    pub fn new(name: String, attributes: String, children: Arc<Vec<VirtualNode>>) -> VirtualElement {
+    pub fn new(name: String, attributes: Vec<(String, String)>, children: Rc<Vec<VirtualNode>>) -> VirtualElement {
         VirtualElement {
             name,
             attributes,
@@ -52,125 +83,299 @@ impl VirtualElement {
         }
     }
 
-    pub fn render(&self) -> String {
-        let children = self.children.iter()
-            .map(|child| child.kind.render())
-            .collect::<String>();
+    fn attribute(&self, name: &str) -> Option<&String> {
+        self.attributes.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// A text or attribute that reads signals, the DOM is patched directly when the signals change.
+#[derive(Clone)]
+pub enum Binding {
+    Text(Rc<dyn Fn() -> String>),
+    Attribute(String, Rc<dyn Fn() -> String>),
+}
 
-        format!("<{} {}>{}</{}>", self.name, self.attributes, children, self.name)
+impl std::fmt::Debug for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Binding::Text(_) => f.write_str("Text"),
+            Binding::Attribute(name, _) => f.debug_tuple("Attribute").field(name).finish(),
+        }
+    }
+}
+
+impl Binding {
+    fn effect(&self, renderer: Renderer, handle: Handle) -> Effect {
+        let binding = self.clone();
+        let read = match self {
+            Binding::Text(read) | Binding::Attribute(_, read) => read.clone(),
+        };
+
+        Effect::on_change(move || read(), move |value| {
+            let Some(node) = handle.borrow().clone() else { return };
+
+            match &binding {
+                Binding::Text(_) => node.set_node_value(Some(&value)),
+                Binding::Attribute(name, _) => {
+                    let result = node.dyn_ref::<web_sys::Element>()
+                        .ok_or(RenderError::Cast { expected: "Element" })
+                        .and_then(|element| Ok(element.set_attribute(name, &value)?));
+
+                    if let Err(err) = result {
+                        renderer.report(err);
+                    }
+                },
+            }
+        })
+    }
+}
+
+/// An event listener attached to an element. The scope and message are replaced when the element is
+/// patched, so the listener itself never has to be reattached.
+pub(crate) struct Listener {
+    event: String,
+    target: Rc<RefCell<(Path, Rc<dyn Any>)>>,
+    closure: Closure<dyn Fn()>,
+}
+
+impl std::fmt::Debug for Listener {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_tuple("Listener").field(&self.event).finish()
+    }
+}
+
+impl Listener {
+    fn attach(renderer: Renderer, element: &web_sys::Element, event: &str, scope: Path, message: Rc<dyn Any>) -> Result<Listener, RenderError> {
+        let target = Rc::new(RefCell::new((scope, message)));
+
+        let closure = Closure::<dyn Fn()>::new({
+            let target = target.clone();
+
+            move || {
+                let (scope, message) = target.borrow().clone();
+
+                renderer.send(scope, message);
+            }
+        });
+
+        element.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())?;
+
+        Ok(Listener {
+            event: event.to_string(),
+            target,
+            closure,
+        })
+    }
+
+    fn detach(&self, element: &web_sys::Element) -> Result<(), RenderError> {
+        Ok(element.remove_event_listener_with_callback(&self.event, self.closure.as_ref().unchecked_ref())?)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct VirtualNode {
-    callbacks: Arc<Vec<(String, Arc<dyn Any + Send + Sync>)>>,
+    callbacks: Rc<Vec<(String, Rc<dyn Any>)>>,
+    listeners: Rc<RefCell<Vec<Listener>>>,
+    bindings: Rc<Vec<Binding>>,
+    handle: Handle,
+    /// The children of a pending suspense that werent hydrated, the DOM sent by the server is kept instead.
+    skipped: Rc<Cell<bool>>,
     kind: VirtualKind,
+    key: Option<Key>,
     scope: Path,
 }
 
-impl PartialEq for VirtualNode {
-    fn eq(&self, other: &VirtualNode) -> bool {
-        self.kind == other.kind
-    }
-}
-
 impl Default for VirtualNode {
     fn default() -> VirtualNode {
         VirtualNode {
-            callbacks: Arc::new(Vec::new()),
+            callbacks: Rc::new(Vec::new()),
+            listeners: Rc::new(RefCell::new(Vec::new())),
+            bindings: Rc::new(Vec::new()),
+            handle: Rc::new(RefCell::new(None)),
+            skipped: Rc::new(Cell::new(false)),
             kind: VirtualKind::Template(String::new()),
+            key: None,
             scope: Path::new(),
         }
     }
 }
 
 impl VirtualNode {
-    pub(crate) fn new(callbacks: Arc<Vec<(String, Arc<dyn Any + Send + Sync>)>>, kind: VirtualKind, scope: Path) -> VirtualNode {
+    pub(crate) fn new(callbacks: Rc<Vec<(String, Rc<dyn Any>)>>, kind: VirtualKind, scope: Path) -> VirtualNode {
         VirtualNode {
             callbacks,
+            listeners: Rc::new(RefCell::new(Vec::new())),
+            bindings: Rc::new(Vec::new()),
+            handle: Rc::new(RefCell::new(None)),
+            skipped: Rc::new(Cell::new(false)),
             kind,
+            key: None,
             scope,
         }
     }
 
-    fn attach_listener(&self, renderer: Renderer, old_element: web_sys::HtmlElement, event: &str, cb: &Arc<dyn Any + Send + Sync>) -> Result<(), JsValue> {
-        if let Some(parent) = old_element.parent_node() {
-            let new_node = old_element.clone_node_with_deep(true)?;
+    pub(crate) fn with_bindings(self, bindings: Vec<Binding>) -> VirtualNode {
+        VirtualNode {
+            bindings: Rc::new(bindings),
+            ..self
+        }
+    }
 
-            parent.replace_child(&new_node, &old_element)?;
+    /// Match the node by key instead of by position when its siblings are diffed.
+    pub(crate) fn with_key(self, key: Option<Key>) -> VirtualNode {
+        VirtualNode {
+            key,
+            ..self
+        }
+    }
 
-            let scope = self.scope.clone();
-            let cb = cb.clone();
+    /// Create an effect for every binding in the tree, the DOM is patched as long as the effects are alive.
+    pub(crate) fn bind(&self, renderer: &Renderer, effects: &mut Vec<Effect>) {
+        for binding in self.bindings.iter() {
+            effects.push(binding.effect(renderer.clone(), self.handle.clone()));
+        }
 
-            let closure = Closure::<dyn Fn()>::new(move || {
-                fn hook_callback(renderer: &Renderer, scope: &Path, cb: &Arc<dyn Any + Send + Sync>) {
-                    let component = renderer.get(&scope);
+        for child in self.kind.children().iter() {
+            child.bind(renderer, effects);
+        }
+    }
 
-                    component.lock().base_callback(cb, Link::new(renderer.clone(), scope.clone()));
-                }
+    /// The name of the element, [`None`] for text.
+    pub(crate) fn tag(&self) -> Option<&str> {
+        match &self.kind {
+            VirtualKind::Template(_) => None,
+            VirtualKind::Element(element) => Some(&element.name),
+        }
+    }
 
-                hook_callback(&renderer, &scope, &cb);
+    /// The text of a text node with the current value of its signals, [`None`] for elements.
+    pub(crate) fn text(&self) -> Option<String> {
+        let VirtualKind::Template(text) = &self.kind else { return None };
 
-                renderer.render();
+        let bound = self.bindings.iter()
+            .find_map(|binding| match binding {
+                Binding::Text(read) => Some(read()),
+                Binding::Attribute(..) => None,
             });
 
-            new_node.add_event_listener_with_callback(&event, closure.as_ref().unchecked_ref())?;
+        Some(bound.unwrap_or_else(|| text.clone()))
+    }
 
-            closure.forget();
-        }
+    /// The attributes of an element with the current value of their signals.
+    pub(crate) fn attributes(&self) -> Vec<(String, String)> {
+        let VirtualKind::Element(element) = &self.kind else { return Vec::new() };
+
+        element.attributes.iter()
+            .map(|(name, value)| {
+                let bound = self.bindings.iter()
+                    .find_map(|binding| match binding {
+                        Binding::Attribute(bound, read) if bound == name => Some(read()),
+                        _ => None,
+                    });
+
+                (name.clone(), bound.unwrap_or_else(|| value.clone()))
+            })
+            .collect()
+    }
 
-        Ok(())
+    /// The path of the component that rendered the node.
+    pub(crate) fn scope(&self) -> &Path {
+        &self.scope
+    }
+
+    pub(crate) fn children(&self) -> Rc<Vec<VirtualNode>> {
+        self.kind.children()
     }
 
-    fn passover(&self, renderer: Renderer, path: Path, document: &web_sys::Document) {
+    /// The events the node listens to, with the scope and message they are sent to.
+    pub(crate) fn callbacks(&self) -> impl Iterator<Item = (&str, &Path, &Rc<dyn Any>)> {
+        self.callbacks.iter().map(|(event, message)| (event.as_str(), &self.scope, message))
+    }
+
+    /// The DOM node of the node, if it is mounted.
+    fn node(&self) -> Option<web_sys::Node> {
+        self.handle.borrow().clone()
+    }
+
+    /// Create the DOM of the node and its children.
+    fn create(&self, renderer: Renderer, document: &web_sys::Document) -> Result<web_sys::Node, RenderError> {
+        let node = self.build(renderer, document)?;
+
+        self.handle.replace(Some(node.clone()));
+
+        Ok(node)
+    }
+
+    fn build(&self, renderer: Renderer, document: &web_sys::Document) -> Result<web_sys::Node, RenderError> {
         match &self.kind {
-            VirtualKind::Template(template) => if let Ok(element) = renderer.get_element_by_path(&path, document) {
-                let node = document.create_text_node(&template);
+            VirtualKind::Template(template) => Ok(document.create_text_node(template).into()),
+            VirtualKind::Element(element) => {
+                let node = document.create_element(&element.name)?;
 
-                if let Err(_) = element.append_child(&node) {
-                    web_sys::console::error_1(&format!("failed to set template: {:?}", path).into());
+                for (name, value) in element.attributes.iter() {
+                    node.set_attribute(name, value)?;
                 }
-            },
-            _ => {},
-        }
 
-        for (index, child) in self.kind.children().iter().enumerate() {
-            let path = path.clone().concat(PathNode::new(index, String::from("virtual_node")));
+                self.listen(renderer.clone(), &node)?;
 
-            for (event, cb) in child.callbacks.iter() {
-                if let Ok(element) = renderer.get_element_by_path(&path, document) {
-                    if let Err(_) = child.attach_listener(renderer.clone(), element, event, cb) {
-                        web_sys::console::error_1(&format!("failed to attach listener: {:?}", path).into());
-                    }
+                for child in element.children.iter() {
+                    node.append_child(&child.create(renderer.clone(), document)?)?;
                 }
-            }
 
-            child.passover(renderer.clone(), path, document);
+                Ok(node.into())
+            },
         }
     }
 
-    pub fn reconcile(&self, renderer: Renderer, other: &VirtualNode, path: Path, document: &web_sys::Document) -> Result<(), JsValue> {
-        if self.kind.children() != other.kind.children() {
-            let children = self.kind.children()
-                .iter()
-                .map(|child| child.kind.render())
-                .collect::<String>();
+    /// Attach a listener for every callback of the node.
+    fn listen(&self, renderer: Renderer, element: &web_sys::Element) -> Result<(), RenderError> {
+        let listeners = self.callbacks.iter()
+            .map(|(event, message)| Listener::attach(renderer.clone(), element, event, self.scope.clone(), message.clone()))
+            .collect::<Result<Vec<Listener>, RenderError>>()?;
 
-            let element = renderer.get_element_by_path(&path, document)?;
+        *self.listeners.borrow_mut() = listeners;
 
-            element.set_inner_html(&children);
+        Ok(())
+    }
 
-            self.passover(renderer, path, document);
-        } else {
-            for (index, (a, b)) in self.kind.children().iter().zip(other.kind.children().iter()).enumerate() {
-                let path = path.clone().concat(PathNode::new(index, String::from("virtual_node")));
+    /// The html of the children of the node, text and attribute values are escaped.
+    pub(crate) fn inner_html(&self) -> String {
+        let mut html = String::new();
 
-                a.reconcile(renderer.clone(), &b, path, &document)?;
-            }
-        }
+        markup::write_nodes(&self.kind.children(), false, &mut html);
 
-        Ok(())
+        html
     }
-}
 
+    /// The html of the node itself.
+    pub(crate) fn outer_html(&self) -> String {
+        let mut html = String::new();
+
+        markup::write_nodes(std::slice::from_ref(self), false, &mut html);
+
+        html
+    }
+
+    /// Adopt the DOM under `root` rendered by the server instead of creating it, the DOM nodes are retained and
+    /// their listeners attached.
+    pub fn hydrate(&self, renderer: Renderer, root: &web_sys::Node, document: &web_sys::Document) -> Result<(), RenderError> {
+        hydrate::hydrate_children(renderer, root, &self.kind.children(), document)
+    }
+
+    /// Patch the DOM under `root` from `other` to `self`. Nodes that didnt change are left untouched.
+    pub fn reconcile(&self, renderer: Renderer, root: &web_sys::Node, other: &VirtualNode, document: &web_sys::Document) -> Result<(), RenderError> {
+        // Before the first render and after a failed render the previous tree is empty, anything in the root is
+        // cleared so it is rebuilt from scratch.
+        if let VirtualKind::Template(_) = other.kind {
+            root.set_text_content(None);
+        }
+
+        let mut patches = Vec::new();
 
+        patch::diff_children(root, &other.kind.children(), &self.kind.children(), &mut patches);
+
+        patch::apply(patches, renderer, document)
+    }
+}
@@ -0,0 +1,366 @@
+use crate::frontend::virtual_dom::{VirtualKind, VirtualNode, Handle, Key};
+use crate::frontend::render::{Renderer, RenderError};
+use crate::frontend::suspense::PENDING;
+
+use wasm_bindgen::prelude::*;
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+
+/// A single change to the DOM, produced by diffing two virtual trees.
+///
+/// Patches address the DOM nodes retained by the old tree directly. Nodes are inserted before the node of
+/// a sibling, which is resolved when the patch is applied so it can be a node created by an earlier patch.
+#[derive(Debug)]
+pub(crate) enum Patch {
+    /// Create the DOM of a node and insert it into `parent` before `before`, or at the end.
+    Create {
+        parent: web_sys::Node,
+        node: VirtualNode,
+        before: Option<Handle>,
+    },
+
+    /// Move a node within `parent` before `before`, or to the end.
+    Move {
+        parent: web_sys::Node,
+        node: web_sys::Node,
+        before: Option<Handle>,
+    },
+
+    /// Remove a node.
+    Remove {
+        node: web_sys::Node,
+    },
+
+    /// Set an attribute of an element.
+    SetAttribute {
+        element: web_sys::Element,
+        name: String,
+        value: String,
+    },
+
+    /// Remove an attribute of an element.
+    RemoveAttribute {
+        element: web_sys::Element,
+        name: String,
+    },
+
+    /// Replace the DOM children of an element that werent hydrated with the DOM of `children`.
+    ReplaceChildren {
+        element: web_sys::Element,
+        children: Rc<Vec<VirtualNode>>,
+    },
+
+    /// Set the text of a template's text node.
+    SetText {
+        node: web_sys::Node,
+        text: String,
+    },
+
+    /// Replace the listeners of an element whose events changed.
+    Listen {
+        element: web_sys::Element,
+        previous: VirtualNode,
+        node: VirtualNode,
+    },
+
+    /// Hand the listeners of an element whose events didnt change on to its new node. The listeners are only
+    /// handed on once every other patch succeeded, so the old tree keeps them if the DOM couldnt be patched.
+    Adopt {
+        previous: VirtualNode,
+        node: VirtualNode,
+    },
+}
+
+/// Diff the children of `parent`.
+///
+/// Keyed children are matched by key and the others by position, a match is only made between nodes that
+/// can be patched into each other. Unmatched old children are removed and unmatched new children created.
+pub(crate) fn diff_children(parent: &web_sys::Node, old: &[VirtualNode], new: &[VirtualNode], patches: &mut Vec<Patch>) {
+    let matches = match_children(old, new, |node| node.node().is_some());
+
+    let mut used = vec![false; old.len()];
+
+    for from in matches.iter().flatten() {
+        used[*from] = true;
+    }
+
+    for (from, _) in used.iter().enumerate().filter(|(_, used)| !**used) {
+        if let Some(node) = old[from].node() {
+            patches.push(Patch::Remove { node });
+        }
+    }
+
+    // The order of the DOM children is simulated, so a child is only moved when it isnt in place already.
+    let mut order = (0..old.len())
+        .filter(|from| used[*from])
+        .map(|from| old[from].handle.clone())
+        .collect::<Vec<Handle>>();
+
+    for (index, from) in matches.iter().enumerate() {
+        match from {
+            Some(from) if order.get(index).is_some_and(|handle| Rc::ptr_eq(handle, &old[*from].handle)) => {},
+            Some(from) => {
+                let before = order.get(index).cloned();
+
+                order.retain(|handle| !Rc::ptr_eq(handle, &old[*from].handle));
+                order.insert(index, old[*from].handle.clone());
+
+                if let Some(node) = old[*from].node() {
+                    patches.push(Patch::Move { parent: parent.clone(), node, before });
+                }
+            },
+            None => {
+                let before = order.get(index).cloned();
+
+                order.insert(index, new[index].handle.clone());
+
+                patches.push(Patch::Create { parent: parent.clone(), node: new[index].clone(), before });
+            },
+        }
+    }
+
+    for (index, from) in matches.iter().enumerate() {
+        if let Some(from) = from {
+            diff_node(&old[*from], &new[index], patches);
+        }
+    }
+}
+
+/// Find the old child every new child is patched from, [`None`] if it has to be created.
+///
+/// Only old children that are `mounted` can be patched, and every old child is matched at most once. Keys used by
+/// more than one new child are reported in debug builds.
+fn match_children(old: &[VirtualNode], new: &[VirtualNode], mounted: impl Fn(&VirtualNode) -> bool) -> Vec<Option<usize>> {
+    if cfg!(debug_assertions) {
+        for key in duplicate_keys(new) {
+            duplicate(key);
+        }
+    }
+
+    let keyed = old.iter()
+        .enumerate()
+        .filter_map(|(index, node)| node.key.as_ref().map(|key| (key, index)))
+        .collect::<HashMap<&Key, usize>>();
+
+    let mut used = vec![false; old.len()];
+
+    new.iter()
+        .enumerate()
+        .map(|(index, node)| {
+            let from = match &node.key {
+                Some(key) => keyed.get(key).copied(),
+                None => old.get(index).filter(|old| old.key.is_none()).map(|_| index),
+            };
+
+            let from = from.filter(|from| !used[*from] && mounted(&old[*from]) && old[*from].kind.compatible(&node.kind));
+
+            if let Some(from) = from {
+                used[from] = true;
+            }
+
+            from
+        })
+        .collect()
+}
+
+/// The keys that more than one of `nodes` has, only the first of them can be matched by its key.
+fn duplicate_keys(nodes: &[VirtualNode]) -> Vec<&Key> {
+    let mut seen = HashSet::new();
+
+    nodes.iter()
+        .filter_map(|node| node.key.as_ref())
+        .filter(|key| !seen.insert(*key))
+        .collect()
+}
+
+fn duplicate(key: &Key) {
+    let message = format!("duplicate key {} among siblings, keys should be unique", key);
+
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::warn_1(&message.into());
+
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}", message);
+}
+
+/// Diff two compatible nodes, the DOM of `old` is handed on to `new`.
+fn diff_node(old: &VirtualNode, new: &VirtualNode, patches: &mut Vec<Patch>) {
+    let Some(node) = old.node() else { return };
+
+    new.handle.replace(Some(node.clone()));
+
+    match (&old.kind, &new.kind) {
+        (VirtualKind::Template(previous), VirtualKind::Template(text)) => {
+            if previous != text {
+                patches.push(Patch::SetText { node, text: text.clone() });
+            }
+        },
+        (VirtualKind::Element(previous), VirtualKind::Element(element)) => {
+            let Some(dom) = node.dyn_ref::<web_sys::Element>() else { return };
+
+            for (name, value) in element.attributes.iter().filter(|(name, value)| previous.attribute(name) != Some(value)) {
+                patches.push(Patch::SetAttribute { element: dom.clone(), name: name.clone(), value: value.clone() });
+            }
+
+            for (name, _) in previous.attributes.iter().filter(|(name, _)| element.attribute(name).is_none()) {
+                patches.push(Patch::RemoveAttribute { element: dom.clone(), name: name.clone() });
+            }
+
+            let same_events = old.listeners.borrow().iter()
+                .map(|listener| &listener.event)
+                .eq(new.callbacks.iter().map(|(event, _)| event));
+
+            if same_events {
+                patches.push(Patch::Adopt { previous: old.clone(), node: new.clone() });
+            } else {
+                patches.push(Patch::Listen { element: dom.clone(), previous: old.clone(), node: new.clone() });
+            }
+
+            match old.skipped.get() {
+                // The suspense is still pending, the DOM sent by the server is kept.
+                true if element.attribute(PENDING).is_some() => new.skipped.set(true),
+                true => patches.push(Patch::ReplaceChildren { element: dom.clone(), children: element.children.clone() }),
+                false => diff_children(&node, &previous.children, &element.children, patches),
+            }
+        },
+        _ => unreachable!("only compatible nodes are diffed"),
+    }
+}
+
+/// Apply the patches to the DOM.
+pub(crate) fn apply(patches: Vec<Patch>, renderer: Renderer, document: &web_sys::Document) -> Result<(), RenderError> {
+    let resolve = |before: Option<Handle>| before.and_then(|handle| handle.borrow().clone());
+
+    let mut adopted = Vec::new();
+
+    for patch in patches {
+        match patch {
+            Patch::Create { parent, node, before } => {
+                let created = node.create(renderer.clone(), document)?;
+
+                parent.insert_before(&created, resolve(before).as_ref())?;
+            },
+            Patch::Move { parent, node, before } => {
+                parent.insert_before(&node, resolve(before).as_ref())?;
+            },
+            Patch::Remove { node } => {
+                if let Some(parent) = node.parent_node() {
+                    parent.remove_child(&node)?;
+                }
+            },
+            Patch::SetAttribute { element, name, value } => {
+                element.set_attribute(&name, &value)?;
+            },
+            Patch::RemoveAttribute { element, name } => {
+                element.remove_attribute(&name)?;
+            },
+            Patch::ReplaceChildren { element, children } => {
+                element.set_text_content(None);
+
+                for child in children.iter() {
+                    element.append_child(&child.create(renderer.clone(), document)?)?;
+                }
+            },
+            Patch::SetText { node, text } => {
+                // The text node is updated in place, so a selection inside it survives the update.
+                node.set_node_value(Some(&text));
+            },
+            Patch::Listen { element, previous, node } => {
+                node.listen(renderer.clone(), &element)?;
+
+                for listener in previous.listeners.borrow().iter() {
+                    listener.detach(&element)?;
+                }
+
+                previous.listeners.take();
+            },
+            Patch::Adopt { previous, node } => adopted.push((previous, node)),
+        }
+    }
+
+    for (previous, node) in adopted {
+        for (listener, (_, message)) in previous.listeners.borrow().iter().zip(node.callbacks.iter()) {
+            *listener.target.borrow_mut() = (node.scope.clone(), message.clone());
+        }
+
+        *node.listeners.borrow_mut() = previous.listeners.take();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::virtual_dom::VirtualElement;
+    use crate::frontend::html::path::{Path, PathNode};
+
+    fn element(name: &str, key: Option<&str>) -> VirtualNode {
+        VirtualNode::new(Rc::new(Vec::new()), VirtualKind::Element(VirtualElement::new(name.to_string(), Vec::new(), Rc::new(Vec::new()))), Path::new())
+            .with_key(key.map(|key| Key::Node(key.to_string())))
+    }
+
+    fn text(text: &str) -> VirtualNode {
+        VirtualNode::new(Rc::new(Vec::new()), VirtualKind::Template(text.to_string()), Path::new())
+    }
+
+    fn keyed(keys: &[&str]) -> Vec<VirtualNode> {
+        keys.iter().map(|key| element("li", Some(key))).collect()
+    }
+
+    #[test]
+    fn keyed_children_are_matched_by_key() {
+        let matches = match_children(&keyed(&["a", "b", "c"]), &keyed(&["c", "a", "d"]), |_| true);
+
+        assert_eq!(matches, vec![Some(2), Some(0), None]);
+    }
+
+    #[test]
+    fn unkeyed_children_are_matched_by_position() {
+        let old = vec![text("first"), element("p", None), element("p", Some("keyed"))];
+        let new = vec![text("changed"), element("p", None), element("p", None)];
+
+        assert_eq!(match_children(&old, &new, |_| true), vec![Some(0), Some(1), None]);
+    }
+
+    #[test]
+    fn incompatible_children_are_not_matched() {
+        let old = vec![element("p", None), text("text"), element("li", Some("a"))];
+        let new = vec![element("div", None), element("span", None), element("p", Some("a"))];
+
+        assert_eq!(match_children(&old, &new, |_| true), vec![None, None, None]);
+    }
+
+    #[test]
+    fn duplicate_keys_match_once() {
+        let matches = match_children(&keyed(&["a"]), &keyed(&["a", "a"]), |_| true);
+
+        assert_eq!(matches, vec![Some(0), None]);
+    }
+
+    #[test]
+    fn unmounted_children_are_not_matched() {
+        let matches = match_children(&keyed(&["a", "b"]), &keyed(&["a", "b"]), |node| node.key == Some(Key::Node(String::from("b"))));
+
+        assert_eq!(matches, vec![None, Some(1)]);
+    }
+
+    #[test]
+    fn component_keys_dont_collide_with_node_keys() {
+        let row = PathNode::component(Some(String::from("a")), 0, String::from("Row"));
+
+        let old = vec![element("li", Some("Row/a/0"))];
+        let new = vec![element("li", None).with_key(Some(Key::Component(row, 0)))];
+
+        assert_eq!(match_children(&old, &new, |_| true), vec![None]);
+    }
+
+    #[test]
+    fn duplicate_keys_are_found() {
+        let nodes = keyed(&["a", "b", "a", "c", "b"]);
+
+        assert_eq!(duplicate_keys(&nodes), vec![&Key::Node(String::from("a")), &Key::Node(String::from("b"))]);
+    }
+}
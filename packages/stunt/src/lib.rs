@@ -39,7 +39,7 @@
 //!         }
 //!     }
 //!
-//!     fn callback(&mut self, message: &Message) {
+//!     fn callback(&mut self, message: &Message, _link: Link<Self>) {
 //!         match message {
 //!             Message::Add => {
 //!                 self.count += 1;
@@ -66,10 +66,23 @@
 //! }
 //! ```
 
+extern crate self as stunt;
+
 pub mod frontend;
 pub mod backend;
 pub mod prelude;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ssr;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "ssg")]
+pub mod ssg;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
+
+#[cfg(target_arch = "wasm32")]
 mod http;
 
 
@@ -40,6 +40,9 @@ pub use crate::backend::Service;
 /// ### Templates
 /// Templates will render as a Text Node into the DOM, or as html if you template a vector of html.
 ///
+/// [`Signal`](crate::frontend::signal::Signal)s can be used as templates and attributes, writing to the signal
+/// will then update the DOM without running the view.
+///
 /// ```rust,no_run
 /// # use stunt::prelude::*;
 /// # fn main() {
@@ -61,10 +64,95 @@ pub use crate::backend::Service;
 /// }
 /// # ;}
 /// ```
+///
+/// ### Keys
+/// Components and elements are identified by their position among their siblings. A `key` identifies them by
+/// the key instead, so components keep their state when items are inserted, removed or reordered.
+///
+/// ```rust,no_run
+/// # use stunt::prelude::*;
+/// # fn main() {
+/// let items = vec![(1, "first"), (2, "second")];
+///
+/// html! {
+///     <ul>
+///         {
+///             items.iter()
+///                 .map(|(id, name)| html! {
+///                     <li key={ id }>
+///                         { *name }
+///                     </li>
+///                 })
+///                 .collect::<Vec<Html>>()
+///         }
+///     </ul>
+/// }
+/// # ;}
+/// ```
 pub use stunt_macro::html;
 
 /// This macro will implement the [`Properties`] trait for a
 /// named Struct.
 pub use stunt_macro::Properties;
 
+/// This macro turns a function into a [`Component`].
+///
+/// The function takes the properties of the component, or nothing if the component has no properties, and returns
+/// the [`Html`] of the view. The component can have local state with [`hooks`](crate::frontend::hooks).
+///
+/// ```rust,no_run
+/// # use stunt::prelude::*;
+/// #[derive(Properties, Clone)]
+/// pub struct BadgeProperties {
+///     label: String,
+/// }
+///
+/// #[component]
+/// pub fn Badge(properties: &BadgeProperties) -> Html {
+///     html! {
+///         <span class={ "badge" }>
+///             { properties.label.clone() }
+///         </span>
+///     }
+/// }
+///
+/// # fn main() {
+/// html! {
+///     <Badge label={ String::from("new") } />
+/// }
+/// # ;}
+/// ```
+pub use stunt_macro::component;
 
+/// This macro turns the implementation of a [`Component`] into an [island](crate::frontend::island).
+///
+/// An island is hydrated on its own when the page is hydrated with [`Islands`](crate::frontend::island::Islands),
+/// its properties have to be serializable.
+///
+/// ```rust,no_run
+/// # use stunt::prelude::*;
+/// pub struct Like {
+///     liked: bool,
+/// }
+///
+/// #[island]
+/// impl Component for Like {
+///     type Message = ();
+///     type Properties = ();
+///
+///     fn create() -> Like { Like { liked: false } }
+///
+///     fn callback(&mut self, _: &(), _: Link<Like>) {
+///         self.liked = !self.liked;
+///     }
+///
+///     fn view(&self, _: ()) -> Html {
+///         html! {
+///             <button onclick={ () }>
+///                 { if self.liked { "liked" } else { "like" } }
+///             </button>
+///         }
+///     }
+/// }
+/// ```
+pub use stunt_macro::island;
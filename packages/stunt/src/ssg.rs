@@ -0,0 +1,149 @@
+//! Static site generation driven by a [`Routable`] enum.
+//!
+//! Every route is rendered with [`ssr::render_to_string`](crate::ssr::render_to_string) while the router is at the
+//! path of the route, and written to the `index.html` of the directory of its path. Routes without path segments are
+//! generated automatically, routes with segments are passed to [`Site::routes`].
+//!
+//! Static site generation is only available with the `ssg` feature, which adds a dependency on `stunt-router`.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//! use stunt::ssg::Site;
+//! use stunt_router::Routable;
+//!
+//! #[derive(Routable)]
+//! pub enum Route {
+//!     #[at("/")]
+//!     Home,
+//!     #[at("/post/:id")]
+//!     Post {
+//!         id: usize,
+//!     },
+//!     #[not_found]
+//!     #[at("/404")]
+//!     NotFound,
+//! }
+//!
+//! pub struct App;
+//!
+//! impl Component for App {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> App { App }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         match stunt_router::route::<Route>() {
+//!             Route::Home => html! { <h1> { "home" } </h1> },
+//!             Route::Post { id } => html! { <h1> { format!("post {}", id) } </h1> },
+//!             Route::NotFound => html! { <h1> { "404: Not Found" } </h1> },
+//!         }
+//!     }
+//! }
+//!
+//! fn main() -> std::io::Result<()> {
+//!     // Writes dist/index.html, dist/404/index.html, dist/post/1/index.html and dist/post/2/index.html.
+//!     Site::new("dist")
+//!         .routes((1..=2).map(|id| Route::Post { id }))
+//!         .wrap("<!DOCTYPE html><html><body>", "</body></html>")
+//!         .generate::<App>(())
+//! }
+//! ```
+
+use crate::frontend::Component;
+use crate::ssr;
+
+use stunt_router::Routable;
+
+use std::path::{Component as PathComponent, PathBuf};
+use std::io;
+
+
+/// A static site, generated into a directory.
+pub struct Site<R: Routable> {
+    dist: PathBuf,
+    routes: Vec<R>,
+    before: String,
+    after: String,
+}
+
+impl<R: Routable> Site<R> {
+    /// Create a site generated into `dist`, with every route that has no path segments.
+    pub fn new(dist: impl Into<PathBuf>) -> Site<R> {
+        Site {
+            dist: dist.into(),
+            routes: R::static_routes(),
+            before: String::new(),
+            after: String::new(),
+        }
+    }
+
+    /// Add routes to the site, e.g. the routes with path segments.
+    pub fn routes(mut self, routes: impl IntoIterator<Item = R>) -> Site<R> {
+        self.routes.extend(routes);
+
+        self
+    }
+
+    /// Write `before` ahead of and `after` behind the html of every page, e.g. the rest of the document.
+    pub fn wrap(self, before: impl Into<String>, after: impl Into<String>) -> Site<R> {
+        Site {
+            before: before.into(),
+            after: after.into(),
+            ..self
+        }
+    }
+
+    /// Render the component with properties for every route and write the pages.
+    pub fn generate<T: Component>(self, properties: T::Properties) -> io::Result<()> {
+        for route in self.routes {
+            let path = route.path();
+
+            let html = stunt_router::with_location(&path, || ssr::render_to_string::<T>(properties.clone()));
+
+            let directory = directory(&self.dist, &path)?;
+
+            std::fs::create_dir_all(&directory)?;
+
+            std::fs::write(directory.join("index.html"), format!("{}{}{}", self.before, html, self.after))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The directory of the page at `path`, paths that would escape `dist` are rejected.
+fn directory(dist: &std::path::Path, path: &str) -> io::Result<PathBuf> {
+    let relative = PathBuf::from(path.trim_start_matches('/'));
+
+    if !relative.components().all(|component| matches!(component, PathComponent::Normal(_))) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid route path: {}", path)));
+    }
+
+    Ok(dist.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_are_written_below_dist() {
+        let dist = std::path::Path::new("dist");
+
+        assert_eq!(directory(dist, "/").unwrap(), dist);
+        assert_eq!(directory(dist, "/posts/1").unwrap(), dist.join("posts").join("1"));
+    }
+
+    #[test]
+    fn paths_escaping_dist_are_rejected() {
+        let dist = std::path::Path::new("dist");
+
+        for path in ["/..", "/posts/..", "/posts/../../etc", "/./posts"] {
+            let err = directory(dist, path).unwrap_err();
+
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", path);
+        }
+    }
+}
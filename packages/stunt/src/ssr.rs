@@ -0,0 +1,331 @@
+//! Server-side rendering of components to html.
+//!
+//! The views are run on the server without a DOM, the html they render can be sent as the initial markup of a
+//! page so it is visible and indexable before the application has loaded.
+//!
+//! The outputs of the [`Service`](crate::backend::Service) calls made while rendering are embedded into the html,
+//! so the calls dont have to be repeated when the page is [hydrated](crate::frontend::render::Renderer::hydrate).
+//!
+//! Only the first render of the components is included, [`once`](crate::frontend::Component::once) isnt called and
+//! the children of [`Portal`](crate::frontend::portal::Portal)s are rendered once the application runs in the browser.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use stunt::prelude::*;
+//!
+//! #[derive(Properties, Clone)]
+//! pub struct AppProperties {
+//!     user: String,
+//! }
+//!
+//! pub struct App;
+//!
+//! impl Component for App {
+//!     type Message = ();
+//!     type Properties = AppProperties;
+//!
+//!     fn create() -> App { App }
+//!
+//!     fn view(&self, properties: AppProperties) -> Html {
+//!         html! {
+//!             <h1>
+//!                 { format!("hello {}", properties.user) }
+//!             </h1>
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let html = stunt::ssr::render_to_string::<App>(AppProperties { user: String::from("world") });
+//!
+//!     assert_eq!(html, "<h1>hello world</h1>");
+//! }
+//! ```
+//!
+//! ## Streaming
+//! Pages with slow sections can be streamed with [`render_to_stream`] instead. The first chunk is sent right away and
+//! contains the fallback of every [`Suspense`](crate::frontend::suspense::Suspense), the content of a suspense is sent
+//! in a later chunk once its future resolved and an inline script swaps it into place. The content of the chunks can
+//! resolve in any order, the outputs of the services are sent in the last chunk.
+//!
+//! With the `actix` feature the stream can be returned from a handler directly.
+//!
+//! ```rust,no_run
+//! # use stunt::prelude::*;
+//! # pub struct App;
+//! # impl Component for App {
+//! #     type Message = ();
+//! #     type Properties = ();
+//! #     fn create() -> App { App }
+//! #     fn view(&self, _: ()) -> Html { html! { <div></div> } }
+//! # }
+//! use stunt::ssr::HtmlStream;
+//!
+//! async fn index() -> HtmlStream {
+//!     stunt::ssr::render_to_stream::<App>(())
+//!         .wrap("<!DOCTYPE html><html><body>", "</body></html>")
+//! }
+//! ```
+
+use crate::frontend::html::path::{Path, PathNode};
+use crate::frontend::render::Renderer;
+use crate::frontend::html::Html;
+use crate::frontend::Component;
+use crate::backend::transfer::{self, State};
+
+use futures_util::stream::{self, FuturesUnordered, LocalBoxStream, Stream, StreamExt};
+use futures_util::future::{self, LocalBoxFuture, FutureExt};
+
+use bytes::Bytes;
+
+use std::task::{Context, Poll};
+use std::pin::Pin;
+
+
+/// Moves the content of a resolved suspense from its template into the placeholder, the script removes itself so
+/// the page can be hydrated.
+const SWAP_SCRIPT: &str = "<script>\
+function __stunt_swap(id) {\
+var template = document.querySelector('template[data-stunt-resolved=\"' + id + '\"]');\
+var placeholder = document.querySelector('[data-stunt-suspense=\"' + id + '\"]');\
+if (placeholder) { placeholder.replaceChildren(template.content); }\
+template.remove();\
+document.currentScript.remove();\
+}\
+document.currentScript.remove();\
+</script>";
+
+/// A stream of html chunks, see [`render_to_stream`].
+pub struct HtmlStream {
+    stream: LocalBoxStream<'static, Bytes>,
+}
+
+impl HtmlStream {
+    /// Send `before` ahead of the first chunk and `after` behind the last chunk, e.g. the rest of the document.
+    pub fn wrap(self, before: impl Into<String>, after: impl Into<String>) -> HtmlStream {
+        let before = stream::once(future::ready(Bytes::from(before.into())));
+        let after = stream::once(future::ready(Bytes::from(after.into())));
+
+        HtmlStream {
+            stream: before.chain(self.stream).chain(after).boxed_local(),
+        }
+    }
+}
+
+impl Stream for HtmlStream {
+    type Item = Bytes;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Bytes>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+#[cfg(feature = "actix")]
+impl actix_web::Responder for HtmlStream {
+    type Body = actix_web::body::BoxBody;
+
+    fn respond_to(self, _: &actix_web::HttpRequest) -> actix_web::HttpResponse {
+        actix_web::HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .streaming(self.map(Ok::<Bytes, std::convert::Infallible>))
+    }
+}
+
+/// Render a component with properties to a string of html.
+pub fn render_to_string<T: Component>(properties: T::Properties) -> String {
+    let renderer = Renderer::detached(T::create());
+    let state = State::default();
+
+    renderer.set_root_props(properties);
+
+    let html = transfer::record(&state, || renderer.view_tree().inner_html());
+
+    html + &transfer::script(&state)
+}
+
+/// Render a component with properties to a stream of html chunks.
+pub fn render_to_stream<T: Component>(properties: T::Properties) -> HtmlStream {
+    let renderer = Renderer::detached(T::create());
+    let state = State::default();
+
+    renderer.set_root_props(properties);
+
+    renderer.deferreds().stream();
+
+    let mut shell = transfer::record(&state, || renderer.view_tree().inner_html());
+    let mut pending = FuturesUnordered::new();
+
+    queue(&renderer, &mut pending);
+
+    if !pending.is_empty() {
+        shell.push_str(SWAP_SCRIPT);
+    }
+
+    let chunks = stream::unfold((renderer, pending), |(renderer, mut pending)| async move {
+        let (id, path, html) = pending.next().await?;
+
+        let content = renderer.render_html(html, path).inner_html();
+
+        queue(&renderer, &mut pending);
+
+        let chunk = format!("<template data-stunt-resolved=\"{}\">{}</template><script>__stunt_swap({})</script>", id, content, id);
+
+        Some((Bytes::from(chunk), (renderer, pending)))
+    });
+
+    let script = stream::once(future::lazy({
+        let state = state.clone();
+
+        move |_| Bytes::from(transfer::script(&state))
+    }));
+
+    let mut stream = stream::once(future::ready(Bytes::from(shell)))
+        .chain(chunks)
+        .chain(script)
+        .filter(|chunk| future::ready(!chunk.is_empty()))
+        .boxed_local();
+
+    // The content of a suspense calls its services while the stream is polled.
+    HtmlStream {
+        stream: stream::poll_fn(move |cx| transfer::record(&state, || stream.poll_next_unpin(cx))).boxed_local(),
+    }
+}
+
+/// Queue the content deferred by the last render, the content is rendered below the path of its suspense.
+fn queue(renderer: &Renderer, pending: &mut FuturesUnordered<LocalBoxFuture<'static, (usize, Path, Html)>>) {
+    for (id, scope, future) in renderer.deferreds().take() {
+        let path = scope.concat(PathNode::deferred(id));
+
+        pending.push(future.map(move |html| (id, path, html)).boxed_local());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::suspense::{Deferred, Suspense};
+    use crate::backend::Service;
+
+    use serde::{Deserialize, Serialize};
+
+    use stunt_macro::html;
+
+    use std::task::Waker;
+
+    #[derive(Serialize, Deserialize, Clone)]
+    struct Double(usize);
+
+    impl Service for Double {
+        const PATH: &'static str = "/double";
+
+        type Output = usize;
+
+        fn handle(self) -> usize {
+            self.0 * 2
+        }
+    }
+
+    /// Pending on the first poll, the content of the suspense is then sent in a chunk of its own.
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+
+            self.0 = true;
+            cx.waker().wake_by_ref();
+
+            Poll::Pending
+        }
+    }
+
+    struct Page;
+
+    impl Component for Page {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Page { Page }
+
+        fn view(&self, _: ()) -> Html {
+            let content = Deferred::new(async {
+                YieldNow(false).await;
+
+                let doubled = Double(21).fetch().await.unwrap_or_default();
+
+                html! {
+                    <b>
+                        { doubled }
+                    </b>
+                }
+            });
+
+            html! {
+                <main>
+                    <Suspense content={ content }>
+                        { "loading" }
+                    </Suspense>
+                </main>
+            }
+        }
+    }
+
+    fn poll(stream: &mut HtmlStream) -> Poll<Option<String>> {
+        let mut cx = Context::from_waker(Waker::noop());
+
+        stream.poll_next_unpin(&mut cx).map(|chunk| chunk.map(|chunk| String::from_utf8(chunk.to_vec()).expect("chunks are utf-8")))
+    }
+
+    #[test]
+    fn the_shell_is_streamed_before_the_content() {
+        let mut stream = render_to_stream::<Page>(());
+
+        let Poll::Ready(Some(shell)) = poll(&mut stream) else { panic!("the shell is ready right away") };
+
+        assert!(shell.starts_with("<main><stunt-suspense style=\"display: contents\" data-stunt-suspense=\"0\">loading</stunt-suspense></main>"));
+        assert!(shell.ends_with(SWAP_SCRIPT));
+
+        assert_eq!(poll(&mut stream), Poll::Pending);
+
+        assert_eq!(poll(&mut stream), Poll::Ready(Some(String::from(
+            "<template data-stunt-resolved=\"0\"><b>42</b></template><script>__stunt_swap(0)</script>",
+        ))));
+
+        assert_eq!(poll(&mut stream), Poll::Ready(Some(String::from(
+            "<script type=\"application/json\" data-stunt-state>{\"/double:21\":42}</script>",
+        ))));
+
+        assert_eq!(poll(&mut stream), Poll::Ready(None));
+    }
+
+    struct Plain;
+
+    impl Component for Plain {
+        type Message = ();
+        type Properties = ();
+
+        fn create() -> Plain { Plain }
+
+        fn view(&self, _: ()) -> Html {
+            html! {
+                <p>
+                    { "plain" }
+                </p>
+            }
+        }
+    }
+
+    #[test]
+    fn a_page_without_suspense_is_a_single_chunk() {
+        let mut stream = render_to_stream::<Plain>(()).wrap("<body>", "</body>");
+
+        assert_eq!(poll(&mut stream), Poll::Ready(Some(String::from("<body>"))));
+        assert_eq!(poll(&mut stream), Poll::Ready(Some(String::from("<p>plain</p>"))));
+        assert_eq!(poll(&mut stream), Poll::Ready(Some(String::from("</body>"))));
+        assert_eq!(poll(&mut stream), Poll::Ready(None));
+    }
+}
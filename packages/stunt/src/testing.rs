@@ -0,0 +1,402 @@
+//! A headless renderer for testing components with `cargo test`.
+//!
+//! The [`TestRenderer`] runs components without a DOM, the tree they render is kept in memory where it can be
+//! queried and its event listeners triggered. Messages and renders are handled synchronously, futures spawned by the
+//! components are run with [`TestRenderer::settle`].
+//!
+//! Elements with a [`NodeId`] can be found with [`TestRenderer::find_by_id`], [`NodeId::cast`] returns [`None`]
+//! without a DOM. The children of a [`Portal`](crate::frontend::portal::Portal) aren't rendered.
+//!
+//! ## Example
+//! ```rust
+//! use stunt::prelude::*;
+//! use stunt::testing::TestRenderer;
+//!
+//! pub struct Counter {
+//!     count: usize,
+//! }
+//!
+//! impl Component for Counter {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create() -> Counter { Counter { count: 0 } }
+//!
+//!     fn callback(&mut self, _: &(), _: Link<Counter>) {
+//!         self.count += 1;
+//!     }
+//!
+//!     fn view(&self, _: ()) -> Html {
+//!         html! {
+//!             <div>
+//!                 <button onclick={ () }>
+//!                     { "increment" }
+//!                 </button>
+//!                 <h1 class={ "count" }>
+//!                     { self.count }
+//!                 </h1>
+//!             </div>
+//!         }
+//!     }
+//! }
+//!
+//! let renderer = TestRenderer::mount::<Counter>(());
+//!
+//! renderer.find_by_text("increment")[0].dispatch("click");
+//!
+//! assert_eq!(renderer.find_by_attribute("class", "count")[0].text(), "1");
+//! assert_eq!(renderer.html(), "<div><button>increment</button><h1 class=\"count\">1</h1></div>");
+//! ```
+
+use crate::frontend::html::node_id::NodeId;
+use crate::frontend::virtual_dom::VirtualNode;
+use crate::frontend::snapshot::Snapshot;
+use crate::frontend::render::Renderer;
+use crate::frontend::{Component, Properties};
+
+
+/// A renderer that keeps the rendered tree in memory, see the [module documentation](self).
+pub struct TestRenderer {
+    renderer: Renderer,
+}
+
+impl TestRenderer {
+    /// Mount a component with properties and render it.
+    pub fn mount<T: Component>(properties: T::Properties) -> TestRenderer {
+        let renderer = Renderer::detached(T::create());
+
+        renderer.set_root_props(properties);
+
+        renderer.render();
+
+        TestRenderer {
+            renderer,
+        }
+    }
+
+    /// Replace the properties of the root component and re-render it.
+    ///
+    /// ## Panics
+    /// Panics if `P` isnt the properties type of the root component.
+    pub fn set_props<P: Properties + 'static>(&self, properties: P) {
+        self.renderer.set_props(properties);
+    }
+
+    /// Poll the futures spawned by the components until none of them can make progress anymore, i.e. until every
+    /// future is waiting for a waker that wasnt woken. The messages they send are handled and rendered in the meantime.
+    pub fn settle(&self) {
+        while self.renderer.poll_local() {}
+    }
+
+    /// The html of the rendered tree.
+    pub fn html(&self) -> String {
+        self.renderer.tree().inner_html()
+    }
+
+    /// A [`Snapshot`] of the rendered tree.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::new(&self.renderer.tree())
+    }
+
+    /// Every element whose own text is `text`, ignoring surrounding whitespace.
+    pub fn find_by_text(&self, text: &str) -> Vec<TestNode> {
+        self.find(|node| {
+            let own = node.children().iter()
+                .filter_map(|child| child.text())
+                .collect::<String>();
+
+            node.tag().is_some() && own.trim() == text
+        })
+    }
+
+    /// Every element named `tag`.
+    pub fn find_by_tag(&self, tag: &str) -> Vec<TestNode> {
+        self.find(|node| node.tag() == Some(tag))
+    }
+
+    /// Every element with an attribute `name` set to `value`.
+    pub fn find_by_attribute(&self, name: &str, value: &str) -> Vec<TestNode> {
+        self.find(|node| node.attributes().iter().any(|attribute| attribute.0 == name && attribute.1 == value))
+    }
+
+    /// The element a [`NodeId`] is attached to.
+    pub fn find_by_id(&self, id: NodeId) -> Option<TestNode> {
+        self.find_by_attribute("id", &id.to_string()).into_iter().next()
+    }
+
+    fn find(&self, f: impl Fn(&VirtualNode) -> bool) -> Vec<TestNode> {
+        let mut nodes = Vec::new();
+
+        for child in self.renderer.tree().children().iter() {
+            TestRenderer::collect(child, &f, &mut nodes);
+        }
+
+        nodes.into_iter()
+            .map(|node| TestNode { node, renderer: self.renderer.clone() })
+            .collect()
+    }
+
+    fn collect(node: &VirtualNode, f: &impl Fn(&VirtualNode) -> bool, nodes: &mut Vec<VirtualNode>) {
+        if f(node) {
+            nodes.push(node.clone());
+        }
+
+        for child in node.children().iter() {
+            TestRenderer::collect(child, f, nodes);
+        }
+    }
+}
+
+/// A node of the tree rendered by a [`TestRenderer`].
+///
+/// The node is a snapshot, it isnt updated by later renders.
+pub struct TestNode {
+    node: VirtualNode,
+    renderer: Renderer,
+}
+
+impl TestNode {
+    /// The name of the element, [`None`] for text.
+    pub fn tag(&self) -> Option<&str> {
+        self.node.tag()
+    }
+
+    /// The value of an attribute.
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        self.node.attributes()
+            .into_iter()
+            .find(|attribute| attribute.0 == name)
+            .map(|attribute| attribute.1)
+    }
+
+    /// The text of the node and its descendants.
+    pub fn text(&self) -> String {
+        fn text(node: &VirtualNode) -> String {
+            node.text().unwrap_or_else(|| node.children().iter().map(text).collect())
+        }
+
+        text(&self.node)
+    }
+
+    /// The html of the node.
+    pub fn html(&self) -> String {
+        self.node.outer_html()
+    }
+
+    /// Trigger the listeners of an event, e.g. `click` for `onclick`. The messages are handled and rendered before
+    /// this returns.
+    ///
+    /// ## Panics
+    /// Panics if the node doesnt listen to the event.
+    pub fn dispatch(&self, event: &str) {
+        let targets = self.node.callbacks()
+            .filter(|(name, _, _)| *name == event)
+            .map(|(_, scope, message)| (scope.clone(), message.clone()))
+            .collect::<Vec<_>>();
+
+        assert!(!targets.is_empty(), "{} doesnt listen to {}", self.html(), event);
+
+        for (scope, message) in targets {
+            self.renderer.send(scope, message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    use std::task::{Context, Poll, Waker};
+    use std::cell::RefCell;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::rc::Rc;
+
+    /// Returns pending once and wakes itself.
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut YieldNow>, cx: &mut Context) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+
+            self.0 = true;
+
+            cx.waker().wake_by_ref();
+
+            Poll::Pending
+        }
+    }
+
+    /// A value handed from one future to another, the receiving future is woken once it is sent.
+    #[derive(Clone, Default)]
+    struct Slot(Rc<RefCell<(Option<usize>, Option<Waker>)>>);
+
+    impl Slot {
+        fn send(&self, value: usize) {
+            let waker = {
+                let mut slot = self.0.borrow_mut();
+
+                slot.0 = Some(value);
+                slot.1.take()
+            };
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    impl Future for Slot {
+        type Output = usize;
+
+        fn poll(self: Pin<&mut Slot>, cx: &mut Context) -> Poll<usize> {
+            let mut slot = self.0.borrow_mut();
+
+            match slot.0.take() {
+                Some(value) => Poll::Ready(value),
+                None => {
+                    slot.1 = Some(cx.waker().clone());
+
+                    Poll::Pending
+                },
+            }
+        }
+    }
+
+    #[derive(Properties, Clone)]
+    struct ProfileProperties {
+        name: String,
+        avatar: NodeId,
+    }
+
+    enum Message {
+        Loaded(usize),
+        Sent,
+        Focus,
+    }
+
+    struct Profile {
+        posts: Option<usize>,
+        focused: bool,
+        input: NodeId,
+    }
+
+    impl Component for Profile {
+        type Message = Message;
+        type Properties = ProfileProperties;
+
+        fn create() -> Profile {
+            Profile {
+                posts: None,
+                focused: false,
+                input: NodeId::new(),
+            }
+        }
+
+        fn once(&mut self, link: Link<Profile>) {
+            let slot = Slot::default();
+
+            link.send_future({
+                let slot = slot.clone();
+
+                async move { Message::Loaded(slot.await) }
+            });
+
+            link.send_future(async move {
+                YieldNow(false).await;
+
+                slot.send(3);
+
+                Message::Sent
+            });
+        }
+
+        fn callback(&mut self, message: &Message, _: Link<Profile>) {
+            match message {
+                Message::Loaded(posts) => self.posts = Some(*posts),
+                Message::Sent => {},
+                Message::Focus => self.focused = self.input.cast::<web_sys::Element>().is_none(),
+            }
+        }
+
+        fn view(&self, properties: ProfileProperties) -> Html {
+            html! {
+                <div>
+                    <img id={ properties.avatar } />
+                    <h1>
+                        { properties.name }
+                    </h1>
+                    <p>
+                        { self.posts.map(|posts| posts.to_string()).unwrap_or_default() }
+                    </p>
+                    <input id={ self.input } class={ self.focused } />
+                    <button onclick={ Message::Focus }>
+                        { "focus" }
+                    </button>
+                </div>
+            }
+        }
+    }
+
+    fn mount() -> TestRenderer {
+        TestRenderer::mount::<Profile>(ProfileProperties { name: String::from("ada"), avatar: NodeId::new() })
+    }
+
+    #[test]
+    fn settle_runs_futures_until_they_are_done() {
+        let renderer = mount();
+
+        assert_eq!(renderer.find_by_tag("p")[0].text(), "");
+
+        renderer.settle();
+
+        assert_eq!(renderer.find_by_tag("p")[0].text(), "3");
+    }
+
+    #[test]
+    fn set_props_rerenders_the_root() {
+        let renderer = mount();
+
+        renderer.set_props(ProfileProperties { name: String::from("grace"), avatar: NodeId::new() });
+
+        assert_eq!(renderer.find_by_tag("h1")[0].text(), "grace");
+    }
+
+    #[test]
+    fn find_by_tag_finds_every_element() {
+        let renderer = mount();
+
+        assert_eq!(renderer.find_by_tag("div").len(), 1);
+        assert_eq!(renderer.find_by_tag("button")[0].text(), "focus");
+        assert!(renderer.find_by_tag("span").is_empty());
+    }
+
+    #[test]
+    fn node_ids_are_found_without_a_dom() {
+        let avatar = NodeId::new();
+        let renderer = TestRenderer::mount::<Profile>(ProfileProperties { name: String::from("ada"), avatar });
+
+        assert_eq!(renderer.find_by_id(avatar).and_then(|node| node.tag().map(String::from)).as_deref(), Some("img"));
+        assert!(renderer.find_by_id(NodeId::new()).is_none());
+    }
+
+    #[test]
+    fn node_ids_cast_to_nothing_without_a_dom() {
+        let renderer = mount();
+
+        renderer.find_by_text("focus")[0].dispatch("click");
+
+        assert_eq!(renderer.find_by_tag("input")[0].attribute("class").as_deref(), Some("true"));
+    }
+
+    #[test]
+    #[should_panic(expected = "doesnt listen to input")]
+    fn dispatch_rejects_missing_events() {
+        mount().find_by_tag("button")[0].dispatch("input");
+    }
+}